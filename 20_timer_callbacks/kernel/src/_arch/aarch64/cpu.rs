@@ -19,11 +19,33 @@ use aarch64_cpu::asm;
 
 pub use asm::nop;
 
+/// Put the core to sleep until an IRQ is pending.
+///
+/// Also wakes up if IRQs are masked on the core. The IRQ is then taken once they get unmasked.
+#[inline(always)]
+pub fn wait_for_interrupt() {
+    asm::wfi()
+}
+
+/// Put the core to sleep until an event is signaled, e.g. by `send_event()` on another core.
+///
+/// Can return spuriously, so the wake-up condition must be checked again afterwards.
+#[inline(always)]
+pub fn wait_for_event() {
+    asm::wfe()
+}
+
+/// Signal an event to all cores.
+#[inline(always)]
+pub fn send_event() {
+    asm::sev()
+}
+
 /// Pause execution on the core.
 #[inline(always)]
 pub fn wait_forever() -> ! {
     loop {
-        asm::wfe()
+        wait_for_event()
     }
 }
 
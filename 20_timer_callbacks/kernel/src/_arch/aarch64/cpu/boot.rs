@@ -88,7 +88,7 @@ unsafe fn prepare_backtrace_reset() {
 
 /// The Rust entry of the `kernel` binary.
 ///
-/// The function is called from the assembly `_start` function.
+/// The function is called from the assembly `_start` and `_start_secondary` functions.
 ///
 /// # Safety
 ///
@@ -103,3 +103,29 @@ _start:
 .size	_start, . - _start
 .type	_start, function
 .global	_start
+
+//------------------------------------------------------------------------------
+// fn _start_secondary()
+//------------------------------------------------------------------------------
+_start_secondary:
+	// Only proceed if the core executes in EL2. Park it otherwise.
+	mrs	x0, CurrentEL
+	cmp	x0, {CONST_CURRENTEL_EL2}
+	b.ne	.L_parking_loop
+
+	// The MMU is still off, so this reads the physical copy of the arguments, which the releasing
+	// core has cleaned to the point of coherency.
+	ADR_REL	x4, SECONDARY_BOOT_ARGS // provided by aarch64/cpu/smp.rs
+	ldr	x3, [x4]
+	mov	sp, x3
+
+	// Same arguments as for the boot core, except for the stack and the Rust entry.
+	ldr	x0, PHYS_KERNEL_TABLES_BASE_ADDR
+	ldr	x1, [x4, #8]
+	ADR_ABS	x2, kernel_init_secondary // provided by aarch64/cpu/smp.rs
+
+	b	_start_rust
+
+.size	_start_secondary, . - _start_secondary
+.type	_start_secondary, function
+.global	_start_secondary
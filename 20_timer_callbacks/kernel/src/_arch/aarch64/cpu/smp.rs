@@ -11,9 +11,76 @@
 //!
 //! crate::cpu::smp::arch_smp
 
+use crate::{
+    cpu, exception,
+    memory::{self, cache, Address, Virtual},
+    time,
+};
 use aarch64_cpu::registers::*;
+use core::{
+    cell::UnsafeCell,
+    mem::size_of,
+    ptr::addr_of,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 use tock_registers::interfaces::Readable;
 
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Arguments for a secondary core on its way from `_start_secondary()` into Rust.
+///
+/// `boot.s` reads the first two fields by offset.
+#[repr(C)]
+struct SecondaryBootArgs {
+    phys_stack_end_exclusive_addr: u64,
+    virt_stack_end_exclusive_addr: u64,
+    entry: Option<fn() -> !>,
+}
+
+/// How long a released core may take to report in.
+const START_TIMEOUT: Duration = Duration::from_secs(1);
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+/// Only one core is released at a time, so a single set of arguments suffices.
+#[no_mangle]
+static mut SECONDARY_BOOT_ARGS: SecondaryBootArgs = SecondaryBootArgs {
+    phys_stack_end_exclusive_addr: 0,
+    virt_stack_end_exclusive_addr: 0,
+    entry: None,
+};
+
+/// Set by a released core once it runs Rust code with the MMU on.
+static SECONDARY_CORE_UP: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// The counterpart of `kernel_init()` for the secondary cores.
+///
+/// # Safety
+///
+/// - Only a released secondary core must run this function, and only once.
+#[no_mangle]
+unsafe fn kernel_init_secondary() -> ! {
+    exception::handling_init();
+
+    // Fetch the entry before reporting in. Afterwards, the arguments may be reused.
+    let entry = SECONDARY_BOOT_ARGS.entry;
+    SECONDARY_CORE_UP.store(true, Ordering::Release);
+
+    match entry {
+        Some(entry) => entry(),
+        None => cpu::wait_forever(),
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
@@ -28,3 +95,69 @@ where
 
     T::from((MPIDR_EL1.get() & CORE_MASK) as u8)
 }
+
+/// Return the raw multiprocessor affinity register of the executing core.
+#[inline(always)]
+pub fn mpidr() -> u64 {
+    MPIDR_EL1.get()
+}
+
+/// Release a parked secondary core and wait until it executes `entry`.
+///
+/// # Safety
+///
+/// - `release_addr` must be the core's spin table entry.
+/// - The stack must be unused and physically contiguous.
+/// - Must not be called concurrently.
+pub unsafe fn start_core(
+    release_addr: Address<Virtual>,
+    virt_stack_start_addr: Address<Virtual>,
+    stack_size: usize,
+    entry: fn() -> !,
+) -> Result<(), &'static str> {
+    // Provided by boot.s.
+    extern "Rust" {
+        static _start_secondary: UnsafeCell<()>;
+    }
+
+    let phys_entry_addr = memory::mmu::virt_to_phys(Address::new(_start_secondary.get() as usize))
+        .ok_or("Secondary core entry is not mapped")?;
+    let phys_stack_start_addr =
+        memory::mmu::virt_to_phys(virt_stack_start_addr).ok_or("Stack is not mapped")?;
+
+    SECONDARY_BOOT_ARGS = SecondaryBootArgs {
+        phys_stack_end_exclusive_addr: (phys_stack_start_addr + stack_size).as_usize() as u64,
+        virt_stack_end_exclusive_addr: (virt_stack_start_addr + stack_size).as_usize() as u64,
+        entry: Some(entry),
+    };
+    SECONDARY_CORE_UP.store(false, Ordering::Relaxed);
+
+    // The released core starts with its MMU and caches off. Push the arguments out to memory, and
+    // make sure that no dirty line of this core overwrites the new stack later on.
+    cache::clean_range(
+        Address::new(addr_of!(SECONDARY_BOOT_ARGS) as usize),
+        size_of::<SecondaryBootArgs>(),
+    );
+    cache::clean_and_invalidate_range(virt_stack_start_addr, stack_size);
+
+    let release_ptr = release_addr.as_usize() as *mut u64;
+    core::ptr::write_volatile(release_ptr, phys_entry_addr.as_usize() as u64);
+    cache::clean_range(release_addr, size_of::<u64>());
+
+    // The firmware parks the core with `wfe`.
+    cpu::send_event();
+
+    let deadline = time::time_manager().uptime() + START_TIMEOUT;
+    while !SECONDARY_CORE_UP.load(Ordering::Acquire) {
+        if time::time_manager().uptime() > deadline {
+            // Prevent a late start from picking up arguments that are meant for another core.
+            SECONDARY_BOOT_ARGS.entry = None;
+
+            return Err("Core did not come up in time");
+        }
+
+        cpu::nop();
+    }
+
+    Ok(())
+}
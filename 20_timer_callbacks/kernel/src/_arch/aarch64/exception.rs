@@ -11,7 +11,7 @@
 //!
 //! crate::exception::arch_exception
 
-use crate::{exception, memory, symbols};
+use crate::{bsp, exception, memory, symbols};
 use aarch64_cpu::{asm::barrier, registers::*};
 use core::{arch::global_asm, cell::UnsafeCell, fmt};
 use tock_registers::{
@@ -198,11 +198,32 @@ impl EsrEL1 {
         self.0.read_as_enum(ESR_EL1::EC)
     }
 
-    #[cfg(feature = "test_build")]
     #[inline(always)]
     fn iss(&self) -> u64 {
         self.0.read(ESR_EL1::ISS)
     }
+
+    /// Decode the fault status code of an instruction or data abort.
+    ///
+    /// Returns the kind of fault and, for faults that occured during a translation table walk, the
+    /// lookup level.
+    fn abort_cause(&self) -> (&'static str, Option<u64>) {
+        let fsc = self.iss() & 0b11_1111;
+        let level = fsc & 0b11;
+
+        match fsc >> 2 {
+            0b0000 => ("address size fault", Some(level)),
+            0b0001 => ("translation fault", Some(level)),
+            0b0010 => ("access flag fault", Some(level)),
+            0b0011 => ("permission fault", Some(level)),
+            _ => match fsc {
+                0b01_0000 => ("synchronous external abort", None),
+                0b10_0001 => ("alignment fault", None),
+                0b11_0000 => ("TLB conflict abort", None),
+                _ => ("unknown fault", None),
+            },
+        }
+    }
 }
 
 /// Human readable ESR_EL1.
@@ -233,6 +254,18 @@ impl ExceptionContext {
         self.esr_el1.exception_class()
     }
 
+    /// Returns the kind of abort if the exception was an instruction or data abort.
+    #[inline(always)]
+    fn abort_kind(&self) -> Option<&'static str> {
+        use ESR_EL1::EC::Value::*;
+
+        match self.exception_class()? {
+            InstrAbortLowerEL | InstrAbortCurrentEL => Some("Instruction abort"),
+            DataAbortLowerEL | DataAbortCurrentEL => Some("Data abort"),
+            _ => None,
+        }
+    }
+
     #[inline(always)]
     fn fault_address_valid(&self) -> bool {
         use ESR_EL1::EC::Value::*;
@@ -253,9 +286,47 @@ impl ExceptionContext {
     }
 }
 
+/// Human readable summary of an abort, with a hint if the address is close to a known mapping.
+fn write_abort_summary(f: &mut fmt::Formatter, kind: &str, exc: &ExceptionContext) -> fmt::Result {
+    let far = FAR_EL1.get() as usize;
+
+    write!(f, "{} at VA {:#018x}, cause: ", kind, far)?;
+    match exc.esr_el1.abort_cause() {
+        (cause, Some(level)) => writeln!(f, "{} level {}", cause, level)?,
+        (cause, None) => writeln!(f, "{}", cause)?,
+    }
+
+    let far = memory::Address::new(far);
+    let max_distance = bsp::memory::mmu::KernelGranule::SIZE;
+    if let Some((name, region)) = memory::mmu::kernel_find_nearby_mapping(far, max_distance) {
+        let relation = if region.contains(far) {
+            "inside"
+        } else if far < region.start_addr() {
+            "just below"
+        } else {
+            "just above"
+        };
+
+        writeln!(
+            f,
+            "      Hint: Address is {} the mapping of {} at {}..{}",
+            relation,
+            name,
+            region.start_addr(),
+            region.end_exclusive_page_addr().into_inner()
+        )?;
+    }
+
+    writeln!(f)
+}
+
 /// Human readable print of the exception context.
 impl fmt::Display for ExceptionContext {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(kind) = self.abort_kind() {
+            write_abort_summary(f, kind, self)?;
+        }
+
         writeln!(f, "{}", self.esr_el1)?;
 
         if self.fault_address_valid() {
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Architectural cache maintenance.
+//!
+//! # Orientation
+//!
+//! Since arch modules are imported into generic modules using the path attribute, the path of this
+//! file is:
+//!
+//! crate::memory::cache::arch_cache
+
+use crate::memory::{Address, Virtual};
+use aarch64_cpu::asm::barrier;
+use core::{arch::asm, ops::Range};
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the range of cache line addresses that cover the given range.
+fn line_range(start: Address<Virtual>, len: usize, line_size: usize) -> Range<usize> {
+    let first = start.as_usize() & !(line_size - 1);
+    let end_exclusive = (start.as_usize() + len + (line_size - 1)) & !(line_size - 1);
+
+    first..end_exclusive
+}
+
+/// Wait for the maintenance operations to complete.
+#[inline(always)]
+fn complete() {
+    barrier::dsb(barrier::SY);
+    barrier::isb(barrier::SY);
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// The smallest data cache line size in bytes, as reported by CTR_EL0.
+pub fn dcache_line_size() -> usize {
+    let ctr: u64;
+    unsafe { asm!("mrs {}, ctr_el0", out(reg) ctr, options(nomem, nostack)) };
+
+    // DminLine, bits [19:16], is the log2 of the number of 4-byte words.
+    4 << ((ctr >> 16) & 0xf)
+}
+
+/// Write dirty cache lines of the range back to memory.
+///
+/// Use before a device reads the range via DMA.
+pub fn clean_range(start: Address<Virtual>, len: usize) {
+    let line_size = dcache_line_size();
+
+    for addr in line_range(start, len, line_size).step_by(line_size) {
+        unsafe { asm!("dc cvac, {}", in(reg) addr, options(nostack)) };
+    }
+
+    complete();
+}
+
+/// Discard the cache lines of the range, so that the next access reads from memory.
+///
+/// Use after a device wrote the range via DMA.
+///
+/// Cache lines are invalidated as a whole. If the range does not start and end on a cache line
+/// boundary, the first and last line also hold data outside of the range, which would be lost. To
+/// be conservative, these lines are cleaned and invalidated instead. This still writes back stale
+/// data over the parts of these lines that are inside the range, so DMA buffers should be aligned
+/// to and sized in multiples of `dcache_line_size()`.
+pub fn invalidate_range(start: Address<Virtual>, len: usize) {
+    let line_size = dcache_line_size();
+    let range_start = start.as_usize();
+    let range_end_exclusive = range_start + len;
+
+    for addr in line_range(start, len, line_size).step_by(line_size) {
+        let partial = (addr < range_start) || (addr + line_size > range_end_exclusive);
+
+        if partial {
+            unsafe { asm!("dc civac, {}", in(reg) addr, options(nostack)) };
+        } else {
+            unsafe { asm!("dc ivac, {}", in(reg) addr, options(nostack)) };
+        }
+    }
+
+    complete();
+}
+
+/// Write dirty cache lines of the range back to memory and discard them afterwards.
+pub fn clean_and_invalidate_range(start: Address<Virtual>, len: usize) {
+    let line_size = dcache_line_size();
+
+    for addr in line_range(start, len, line_size).step_by(line_size) {
+        unsafe { asm!("dc civac, {}", in(reg) addr, options(nostack)) };
+    }
+
+    complete();
+}
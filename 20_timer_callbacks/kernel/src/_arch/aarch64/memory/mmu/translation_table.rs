@@ -24,7 +24,8 @@ use crate::{
         Address, Physical, Virtual,
     },
 };
-use core::convert;
+use aarch64_cpu::asm::barrier;
+use core::{arch::asm, convert};
 use tock_registers::{
     interfaces::{Readable, Writeable},
     register_bitfields,
@@ -348,6 +349,10 @@ impl<const NUM_TABLES: usize, const START_FROM_TOP: bool>
         let mut addr = virt_page_addr.into_inner();
 
         if START_FROM_TOP {
+            if addr < Self::START_FROM_TOP_OFFSET {
+                return Err("Virtual page is out of bounds of translation table");
+            }
+
             addr = addr - Self::START_FROM_TOP_OFFSET;
         }
 
@@ -392,6 +397,15 @@ impl<const NUM_TABLES: usize, const START_FROM_TOP: bool>
         *desc = *new_desc;
         Ok(())
     }
+
+    /// Invalidate the TLB entries of a page on all cores of the inner shareable domain.
+    #[inline(always)]
+    fn invalidate_tlb_page(virt_page_addr: PageAddress<Virtual>) {
+        // The operand holds bits [55:12] of the address, independent of the granule size.
+        let operand = virt_page_addr.into_inner().as_usize() >> 12;
+
+        unsafe { asm!("tlbi vaae1is, {}", in(reg) operand, options(nostack)) };
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -449,6 +463,35 @@ impl<const NUM_TABLES: usize, const START_FROM_TOP: bool>
         Ok(())
     }
 
+    unsafe fn unmap_at(&mut self, virt_region: &MemoryRegion<Virtual>) -> Result<(), &'static str> {
+        assert!(self.initialized, "Translation tables not initialized");
+
+        // Check first, so that a failure does not leave the region partially unmapped.
+        for virt_page_addr in virt_region.into_iter() {
+            let page_desc = self.page_descriptor_from_page_addr(virt_page_addr)?;
+
+            if !page_desc.is_valid() {
+                return Err("Virtual page is not mapped");
+            }
+        }
+
+        for virt_page_addr in virt_region.into_iter() {
+            let (lvl2_index, lvl3_index) = self.lvl2_lvl3_index_from_page_addr(virt_page_addr)?;
+            self.lvl3[lvl2_index][lvl3_index] = PageDescriptor::new_zeroed();
+        }
+
+        // Make the descriptor updates visible to the table walker before dropping stale TLB
+        // entries, and wait for the invalidation to finish before returning.
+        barrier::dsb(barrier::ISHST);
+        for virt_page_addr in virt_region.into_iter() {
+            Self::invalidate_tlb_page(virt_page_addr);
+        }
+        barrier::dsb(barrier::ISH);
+        barrier::isb(barrier::SY);
+
+        Ok(())
+    }
+
     fn try_virt_page_addr_to_phys_page_addr(
         &self,
         virt_page_addr: PageAddress<Virtual>,
@@ -19,6 +19,7 @@ use aarch64_cpu::{asm::barrier, registers::*};
 use core::{
     num::{NonZeroU128, NonZeroU32, NonZeroU64},
     ops::{Add, Div},
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
     time::Duration,
 };
 use tock_registers::interfaces::{ReadWriteable, Readable, Writeable};
@@ -41,11 +42,24 @@ struct GenericTimerCounterValue(u64);
 #[no_mangle]
 static ARCH_TIMER_COUNTER_FREQUENCY: NonZeroU32 = NonZeroU32::MIN;
 
+/// If non-zero, used instead of ARCH_TIMER_COUNTER_FREQUENCY.
+static ARCH_TIMER_COUNTER_FREQUENCY_OVERRIDE: AtomicU32 = AtomicU32::new(0);
+
+/// The counter value at `mark_init()`. Kept as a counter value instead of a duration, so that a
+/// later frequency override applies to it, too.
+static COUNTER_VALUE_AT_INIT: AtomicU64 = AtomicU64::new(0);
+
 //--------------------------------------------------------------------------------------------------
 // Private Code
 //--------------------------------------------------------------------------------------------------
 
 fn arch_timer_counter_frequency() -> NonZeroU32 {
+    if let Some(frequency) =
+        NonZeroU32::new(ARCH_TIMER_COUNTER_FREQUENCY_OVERRIDE.load(Ordering::Relaxed))
+    {
+        return frequency;
+    }
+
     // Read volatile is needed here to prevent the compiler from optimizing
     // ARCH_TIMER_COUNTER_FREQUENCY away.
     //
@@ -120,6 +134,25 @@ impl TryFrom<Duration> for GenericTimerCounterValue {
     }
 }
 
+/// Like the `TryFrom<Duration>` conversion, but rounds up to the next full counter tick.
+///
+/// Once the counter reached the result, at least `duration` has passed.
+fn counter_value_ceil(duration: Duration) -> Result<GenericTimerCounterValue, &'static str> {
+    if duration > max_duration() {
+        return Err("Conversion error. Duration too big");
+    }
+
+    let frequency: u128 = u32::from(arch_timer_counter_frequency()) as u128;
+    let duration: u128 = duration.as_nanos();
+
+    // See the `TryFrom<Duration>` conversion for why this is safe.
+    let counter_value =
+        unsafe { duration.unchecked_mul(frequency) }.div_ceil(u64::from(NANOSEC_PER_SEC) as u128);
+
+    // Rounding up can not exceed u64::MAX, because max_duration() was rounded down from it.
+    Ok(GenericTimerCounterValue(counter_value as u64))
+}
+
 #[inline(always)]
 fn read_cntpct() -> GenericTimerCounterValue {
     // Prevent that the counter is read ahead of time due to out-of-order execution.
@@ -138,6 +171,16 @@ pub fn resolution() -> Duration {
     Duration::from(GenericTimerCounterValue(1))
 }
 
+/// The effective counter frequency in Hz.
+pub fn frequency() -> u64 {
+    u32::from(arch_timer_counter_frequency()) as u64
+}
+
+/// Use `frequency` instead of the value that firmware programmed into CNTFRQ_EL0.
+pub fn set_frequency(frequency: NonZeroU32) {
+    ARCH_TIMER_COUNTER_FREQUENCY_OVERRIDE.store(frequency.get(), Ordering::Relaxed);
+}
+
 /// The uptime since power-on of the device.
 ///
 /// This includes time consumed by firmware and bootloaders.
@@ -145,6 +188,19 @@ pub fn uptime() -> Duration {
     read_cntpct().into()
 }
 
+/// Remember the current counter value as the start of `uptime_since_init()`.
+pub fn mark_init() {
+    COUNTER_VALUE_AT_INIT.store(read_cntpct().0, Ordering::Relaxed);
+}
+
+/// The uptime since `mark_init()`, or since power-on if it was not called yet.
+pub fn uptime_since_init() -> Duration {
+    let start = COUNTER_VALUE_AT_INIT.load(Ordering::Relaxed);
+
+    // Wrapping, so that a counter overflow in between still yields the elapsed ticks.
+    GenericTimerCounterValue(read_cntpct().0.wrapping_sub(start)).into()
+}
+
 /// Spin for a given duration.
 pub fn spin_for(duration: Duration) {
     let curr_counter_value = read_cntpct();
@@ -164,6 +220,24 @@ pub fn spin_for(duration: Duration) {
     while GenericTimerCounterValue(CNTPCT_EL0.get()) < counter_value_target {}
 }
 
+/// Spin until the uptime reached `deadline`.
+pub fn spin_until(deadline: Duration) {
+    // Rounded up, because a truncated target would return up to one tick before the deadline.
+    let counter_value_target = match counter_value_ceil(deadline) {
+        Err(msg) => {
+            warn!("spin_until: {}. Skipping", msg);
+            return;
+        }
+        Ok(val) => val,
+    };
+
+    // Busy wait. Returns right away if the deadline has already passed.
+    //
+    // The ISB of [`read_cntpct`] keeps each read from being done ahead of time, so that returning
+    // is ordered after the deadline.
+    while read_cntpct() < counter_value_target {}
+}
+
 /// The associated IRQ number.
 pub const fn timeout_irq() -> exception::asynchronous::IRQNumber {
     bsp::exception::asynchronous::irq_map::ARM_NS_PHYSICAL_TIMER
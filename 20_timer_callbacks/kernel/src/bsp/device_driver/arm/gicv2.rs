@@ -84,7 +84,7 @@ use crate::{
     cpu, driver, exception,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::InitStateLock,
+    synchronization::Spinlock,
 };
 use alloc::vec::Vec;
 
@@ -109,8 +109,12 @@ pub struct GICv2 {
     /// The CPU Interface.
     gicc: gicc::GICC,
 
-    /// Stores registered IRQ handlers. Writable only during kernel init. RO afterwards.
-    handler_table: InitStateLock<HandlerTable>,
+    /// Stores registered IRQ handlers. Writable at runtime, so that handlers can be registered
+    /// after kernel init, too.
+    handler_table: Spinlock<HandlerTable>,
+
+    /// Number of dispatches per IRQ.
+    irq_counts: exception::asynchronous::IRQCounts,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -134,7 +138,8 @@ impl GICv2 {
         Self {
             gicd: gicd::GICD::new(gicd_mmio_start_addr),
             gicc: gicc::GICC::new(gicc_mmio_start_addr),
-            handler_table: InitStateLock::new(Vec::new()),
+            handler_table: Spinlock::new(Vec::new()),
+            irq_counts: exception::asynchronous::IRQCounts::new(),
         }
     }
 }
@@ -142,7 +147,7 @@ impl GICv2 {
 //------------------------------------------------------------------------------
 // OS Interface Code
 //------------------------------------------------------------------------------
-use synchronization::interface::ReadWriteEx;
+use synchronization::interface::Mutex;
 
 impl driver::interface::DeviceDriver for GICv2 {
     type IRQNumberType = IRQNumber;
@@ -153,7 +158,8 @@ impl driver::interface::DeviceDriver for GICv2 {
 
     unsafe fn init(&self) -> Result<(), &'static str> {
         self.handler_table
-            .write(|table| table.resize(IRQNumber::MAX_INCLUSIVE + 1, None));
+            .lock(|table| table.resize(IRQNumber::MAX_INCLUSIVE + 1, None));
+        self.irq_counts.init(IRQNumber::MAX_INCLUSIVE);
 
         if bsp::cpu::BOOT_CORE_ID == cpu::smp::core_id() {
             self.gicd.boot_core_init();
@@ -173,7 +179,7 @@ impl exception::asynchronous::interface::IRQManager for GICv2 {
         &self,
         irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
     ) -> Result<(), &'static str> {
-        self.handler_table.write(|table| {
+        self.handler_table.lock(|table| {
             let irq_number = irq_handler_descriptor.number().get();
 
             if table[irq_number].is_some() {
@@ -204,29 +210,40 @@ impl exception::asynchronous::interface::IRQManager for GICv2 {
         }
 
         // Call the IRQ handler. Panic if there is none.
-        self.handler_table.read(|table| {
-            match table[irq_number] {
-                None => panic!("No handler registered for IRQ {}", irq_number),
-                Some(descriptor) => {
-                    // Call the IRQ handler. Panics on failure.
-                    descriptor.handler().handle().expect("Error handling IRQ");
-                }
+        //
+        // Copy the descriptor out, so that the handler runs without the table being locked.
+        match self.handler_table.lock(|table| table[irq_number]) {
+            None => panic!("No handler registered for IRQ {}", irq_number),
+            Some(descriptor) => {
+                self.irq_counts.increment(irq_number);
+
+                // Call the IRQ handler. Panics on failure.
+                descriptor.handler().handle().expect("Error handling IRQ");
             }
-        });
+        }
 
         // Signal completion of handling.
         self.gicc.mark_comleted(irq_number as u32, ic);
     }
 
+    fn count(&self, irq_number: &Self::IRQNumberType) -> u64 {
+        self.irq_counts.get(irq_number.get())
+    }
+
     fn print_handler(&self) {
         use crate::info;
 
         info!("      Peripheral handler:");
 
-        self.handler_table.read(|table| {
+        self.handler_table.lock(|table| {
             for (i, opt) in table.iter().skip(32).enumerate() {
                 if let Some(handler) = opt {
-                    info!("            {: >3}. {}", i + 32, handler.name());
+                    info!(
+                        "            IRQ {: >3} ({}): {} handled",
+                        i + 32,
+                        handler.name(),
+                        self.irq_counts.get(i + 32)
+                    );
                 }
             }
         });
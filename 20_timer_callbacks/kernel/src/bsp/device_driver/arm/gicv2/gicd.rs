@@ -6,12 +6,13 @@
 //!
 //! # Glossary
 //!   - SPI - Shared Peripheral Interrupt.
+//!   - PPI - Private Peripheral Interrupt.
 
 use crate::{
     bsp::device_driver::common::MMIODerefWrapper,
     memory::{Address, Virtual},
     state, synchronization,
-    synchronization::IRQSafeNullLock,
+    synchronization::Spinlock,
 };
 use tock_registers::{
     interfaces::{Readable, Writeable},
@@ -53,6 +54,8 @@ register_structs! {
         (0x008 => _reserved1),
         (0x104 => ISENABLER: [ReadWrite<u32>; 31]),
         (0x180 => _reserved2),
+        (0x420 => IPRIORITYR: [ReadWrite<u32>; 248]),
+        (0x800 => _reserved3),
         (0x820 => ITARGETSR: [ReadWrite<u32, ITARGETSR::Register>; 248]),
         (0xC00 => @END),
     }
@@ -64,6 +67,8 @@ register_structs! {
         (0x000 => _reserved1),
         (0x100 => ISENABLER: ReadWrite<u32>),
         (0x104 => _reserved2),
+        (0x400 => IPRIORITYR: [ReadWrite<u32>; 8]),
+        (0x420 => _reserved3),
         (0x800 => ITARGETSR: [ReadOnly<u32, ITARGETSR::Register>; 8]),
         (0x820 => @END),
     }
@@ -82,7 +87,7 @@ type BankedRegisters = MMIODerefWrapper<BankedRegisterBlock>;
 /// Representation of the GIC Distributor.
 pub struct GICD {
     /// Access to shared registers is guarded with a lock.
-    shared_registers: IRQSafeNullLock<SharedRegisters>,
+    shared_registers: Spinlock<SharedRegisters>,
 
     /// Access to banked registers is unguarded.
     banked_registers: BankedRegisters,
@@ -92,6 +97,16 @@ pub struct GICD {
 // Private Code
 //--------------------------------------------------------------------------------------------------
 
+/// Return `reg_val` with the byte-sized field belonging to `irq_num` replaced by `field_val`.
+///
+/// IPRIORITYR and ITARGETSR both pack four IRQs into one u32, one byte per IRQ.
+#[inline(always)]
+fn replace_byte_field(reg_val: u32, irq_num: usize, field_val: u32) -> u32 {
+    let shift = (irq_num % 4) * 8;
+
+    (reg_val & !(0xff << shift)) | ((field_val & 0xff) << shift)
+}
+
 impl SharedRegisters {
     /// Return the number of IRQs that this HW implements.
     #[inline(always)]
@@ -125,6 +140,12 @@ impl SharedRegisters {
 use synchronization::interface::Mutex;
 
 impl GICD {
+    /// The priority that is assigned to every IRQ on enable.
+    ///
+    /// Lower values mean higher priority. Any value works as long as it passes the GICC priority
+    /// mask, which is set to accept all priorities.
+    const DEFAULT_PRIORITY: u32 = 0xA0;
+
     /// Create an instance.
     ///
     /// # Safety
@@ -132,7 +153,7 @@ impl GICD {
     /// - The user must ensure to provide a correct MMIO start address.
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
-            shared_registers: IRQSafeNullLock::new(SharedRegisters::new(mmio_start_addr)),
+            shared_registers: Spinlock::new(SharedRegisters::new(mmio_start_addr)),
             banked_registers: BankedRegisters::new(mmio_start_addr),
         }
     }
@@ -172,6 +193,10 @@ impl GICD {
     }
 
     /// Enable an interrupt.
+    ///
+    /// Before the enable bit is set, the IRQ is assigned `DEFAULT_PRIORITY`. SPIs are additionally
+    /// routed to the executing core, so that an SPI does not rely on `boot_core_init()` having
+    /// covered it.
     pub fn enable(&self, irq_num: &super::IRQNumber) {
         let irq_num = irq_num.get();
 
@@ -180,18 +205,41 @@ impl GICD {
         let enable_reg_index = irq_num >> 5;
         let enable_bit: u32 = 1u32 << (irq_num % 32);
 
+        // Each IPRIORITYR and ITARGETSR holds four byte-sized entries. Shift right by 2 (division
+        // by 4) and arrive at the index for the respective register.
+        let byte_reg_index = irq_num >> 2;
+
         // Check if we are handling a private or shared IRQ.
         match irq_num {
             // Private.
             0..=31 => {
+                let prio_reg = &self.banked_registers.IPRIORITYR[byte_reg_index];
+                prio_reg.set(replace_byte_field(
+                    prio_reg.get(),
+                    irq_num,
+                    Self::DEFAULT_PRIORITY,
+                ));
+
                 let enable_reg = &self.banked_registers.ISENABLER;
                 enable_reg.set(enable_reg.get() | enable_bit);
             }
             // Shared.
             _ => {
                 let enable_reg_index_shared = enable_reg_index - 1;
+                let byte_reg_index_shared = byte_reg_index - 8;
+                let mask = self.local_gic_target_mask();
 
                 self.shared_registers.lock(|regs| {
+                    let prio_reg = &regs.IPRIORITYR[byte_reg_index_shared];
+                    prio_reg.set(replace_byte_field(
+                        prio_reg.get(),
+                        irq_num,
+                        Self::DEFAULT_PRIORITY,
+                    ));
+
+                    let target_reg = &regs.ITARGETSR[byte_reg_index_shared];
+                    target_reg.set(replace_byte_field(target_reg.get(), irq_num, mask));
+
                     let enable_reg = &regs.ISENABLER[enable_reg_index_shared];
                     enable_reg.set(enable_reg.get() | enable_bit);
                 });
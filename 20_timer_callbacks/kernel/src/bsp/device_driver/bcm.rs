@@ -4,12 +4,28 @@
 
 //! BCM driver top level.
 
+mod bcm2xxx_framebuffer;
 mod bcm2xxx_gpio;
 #[cfg(feature = "bsp_rpi3")]
 mod bcm2xxx_interrupt_controller;
+mod bcm2xxx_mailbox;
+mod bcm2xxx_mini_uart;
 mod bcm2xxx_pl011_uart;
+#[cfg(feature = "bsp_rpi3")]
+mod bcm2xxx_rng;
+mod bcm2xxx_spi;
+mod bcm2xxx_system_timer;
+mod bcm2xxx_watchdog;
 
+pub use bcm2xxx_framebuffer::*;
 pub use bcm2xxx_gpio::*;
 #[cfg(feature = "bsp_rpi3")]
 pub use bcm2xxx_interrupt_controller::*;
+pub use bcm2xxx_mailbox::*;
+pub use bcm2xxx_mini_uart::*;
 pub use bcm2xxx_pl011_uart::*;
+#[cfg(feature = "bsp_rpi3")]
+pub use bcm2xxx_rng::*;
+pub use bcm2xxx_spi::*;
+pub use bcm2xxx_system_timer::*;
+pub use bcm2xxx_watchdog::*;
@@ -0,0 +1,253 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Framebuffer Driver.
+//!
+//! The framebuffer is allocated by the VideoCore firmware through the mailbox. If that fails, e.g.
+//! because no display is attached, the driver stays unavailable and drawing does nothing.
+
+use super::{Mailbox, PropertyBuffer};
+use crate::{
+    driver,
+    exception::asynchronous::IRQNumber,
+    memory::{self, mmu::MMIODescriptor, Address, Physical, Virtual},
+    synchronization,
+    synchronization::Spinlock,
+    warn,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+struct FramebufferInner {
+    base: Option<Address<Virtual>>,
+    width: usize,
+    height: usize,
+    pitch: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of a 32 bit per pixel framebuffer.
+///
+/// Colors are given as `0xAARRGGBB`.
+pub struct Framebuffer {
+    mailbox: &'static Mailbox,
+    inner: Spinlock<FramebufferInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl FramebufferInner {
+    const BYTES_PER_PIXEL: usize = 4;
+
+    const fn new() -> Self {
+        Self {
+            base: None,
+            width: 0,
+            height: 0,
+            pitch: 0,
+        }
+    }
+
+    /// Draw a pixel. Coordinates outside of the screen are ignored.
+    fn draw_pixel(&mut self, x: usize, y: usize, color: u32) {
+        self.fill_rect(x, y, 1, 1, color)
+    }
+
+    /// Fill a rectangle. The parts outside of the screen are clipped.
+    fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32) {
+        let base = match self.base {
+            None => return,
+            Some(addr) => addr.as_usize(),
+        };
+
+        let x_end = x.saturating_add(width).min(self.width);
+        let y_end = y.saturating_add(height).min(self.height);
+
+        for row in y..y_end {
+            let row_start = base + row * self.pitch;
+
+            for col in x..x_end {
+                let pixel = (row_start + col * Self::BYTES_PER_PIXEL) as *mut u32;
+
+                unsafe { core::ptr::write_volatile(pixel, color) };
+            }
+        }
+    }
+}
+
+impl Framebuffer {
+    const WIDTH: u32 = 1024;
+    const HEIGHT: u32 = 768;
+    const DEPTH: u32 = 32;
+
+    const TAG_SET_PHYSICAL_SIZE: u32 = 0x0004_8003;
+    const TAG_SET_VIRTUAL_SIZE: u32 = 0x0004_8004;
+    const TAG_SET_DEPTH: u32 = 0x0004_8005;
+    const TAG_SET_PIXEL_ORDER: u32 = 0x0004_8006;
+    const TAG_ALLOCATE_BUFFER: u32 = 0x0004_0001;
+    const TAG_GET_PITCH: u32 = 0x0004_0008;
+
+    /// Blue in the lowest byte, so that a little endian word reads `0xAARRGGBB`.
+    const PIXEL_ORDER_BGR: u32 = 0;
+
+    /// Ask the VideoCore for a framebuffer and map it.
+    unsafe fn allocate(&self) -> Result<(), &'static str> {
+        #[rustfmt::skip]
+        let mut buf = PropertyBuffer([
+            0, 0,
+            Self::TAG_SET_PHYSICAL_SIZE, 8, 0, Self::WIDTH, Self::HEIGHT,
+            Self::TAG_SET_VIRTUAL_SIZE,  8, 0, Self::WIDTH, Self::HEIGHT,
+            Self::TAG_SET_DEPTH,         4, 0, Self::DEPTH,
+            Self::TAG_SET_PIXEL_ORDER,   4, 0, Self::PIXEL_ORDER_BGR,
+            Self::TAG_ALLOCATE_BUFFER,   8, 0, 4096, 0,
+            Self::TAG_GET_PITCH,         4, 0, 0,
+            PropertyBuffer::<32>::TAG_END,
+            0, 0,
+        ]);
+
+        self.mailbox.call(&mut buf)?;
+
+        let (width, height) = (buf.0[5], buf.0[6]);
+        let (vc_base, size) = (buf.0[23], buf.0[24]);
+        let pitch = buf.0[28];
+
+        if buf.0[15] != Self::DEPTH {
+            return Err("32 bit depth not supported");
+        }
+        if (vc_base == 0) || (size == 0) {
+            return Err("No buffer allocated");
+        }
+
+        let mmio_descriptor = MMIODescriptor::new(
+            Address::<Physical>::new(Mailbox::vc_to_arm_addr(vc_base)),
+            size as usize,
+        );
+        let virt_addr = memory::mmu::kernel_map_mmio(Self::COMPATIBLE, &mmio_descriptor)?;
+
+        self.inner.lock(|inner| {
+            inner.base = Some(virt_addr);
+            inner.width = width as usize;
+            inner.height = height as usize;
+            inner.pitch = pitch as usize;
+        });
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Framebuffer {
+    pub const COMPATIBLE: &'static str = "BCM Framebuffer";
+
+    /// Create an instance.
+    ///
+    /// The framebuffer is allocated during driver init.
+    pub const fn new(mailbox: &'static Mailbox) -> Self {
+        Self {
+            mailbox,
+            inner: Spinlock::new(FramebufferInner::new()),
+        }
+    }
+
+    /// Return the width and height in pixels, or `(0, 0)` if no framebuffer is available.
+    pub fn size(&self) -> (usize, usize) {
+        self.inner.lock(|inner| (inner.width, inner.height))
+    }
+
+    /// Concurrency safe version of `FramebufferInner.draw_pixel()`
+    pub fn draw_pixel(&self, x: usize, y: usize, color: u32) {
+        self.inner.lock(|inner| inner.draw_pixel(x, y, color))
+    }
+
+    /// Concurrency safe version of `FramebufferInner.fill_rect()`
+    pub fn fill_rect(&self, x: usize, y: usize, width: usize, height: usize, color: u32) {
+        self.inner
+            .lock(|inner| inner.fill_rect(x, y, width, height, color))
+    }
+
+    /// Fill the whole screen.
+    pub fn clear(&self, color: u32) {
+        self.fill_rect(0, 0, usize::MAX, usize::MAX, color)
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Framebuffer {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        // Not fatal. A headless system can do without.
+        if let Err(x) = self.allocate() {
+            warn!("Framebuffer unavailable: {}", x);
+        }
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::addr_of_mut;
+    use test_macros::kernel_test;
+
+    const WIDTH: usize = 8;
+    const HEIGHT: usize = 4;
+
+    /// Rows are padded by two pixels, like the VideoCore might do.
+    const PITCH_PIXELS: usize = WIDTH + 2;
+
+    /// Plain memory standing in for the framebuffer.
+    static mut MOCK_FRAMEBUFFER: [u32; PITCH_PIXELS * HEIGHT] = [0; PITCH_PIXELS * HEIGHT];
+
+    /// Drawing must respect the pitch and clip at the screen edges.
+    #[kernel_test]
+    fn framebuffer_drawing_clips() {
+        let mock = unsafe { &mut *addr_of_mut!(MOCK_FRAMEBUFFER) };
+        let mut inner = FramebufferInner {
+            base: Some(Address::new(mock.as_ptr() as usize)),
+            width: WIDTH,
+            height: HEIGHT,
+            pitch: PITCH_PIXELS * FramebufferInner::BYTES_PER_PIXEL,
+        };
+
+        inner.fill_rect(6, 2, 10, 10, 0xff00_ff00);
+        inner.draw_pixel(1, 1, 0xffff_0000);
+        inner.draw_pixel(WIDTH, 0, 0xffff_ffff);
+
+        for y in 0..HEIGHT {
+            for x in 0..PITCH_PIXELS {
+                let expected = match (x, y) {
+                    (1, 1) => 0xffff_0000,
+                    (6..=7, 2..=3) => 0xff00_ff00,
+                    _ => 0,
+                };
+
+                assert_eq!(mock[y * PITCH_PIXELS + x], expected);
+            }
+        }
+    }
+}
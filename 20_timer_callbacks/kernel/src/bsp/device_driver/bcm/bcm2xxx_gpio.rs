@@ -10,12 +10,14 @@ use crate::{
     exception::asynchronous::IRQNumber,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::IRQSafeNullLock,
+    synchronization::Spinlock,
+    time,
 };
+use core::{cmp::Ordering, marker::PhantomData, time::Duration};
 use tock_registers::{
-    interfaces::{ReadWriteable, Writeable},
+    interfaces::{ReadWriteable, Readable, Writeable},
     register_bitfields, register_structs,
-    registers::ReadWrite,
+    registers::{ReadOnly, ReadWrite, WriteOnly},
 };
 
 //--------------------------------------------------------------------------------------------------
@@ -36,15 +38,16 @@ register_bitfields! {
         FSEL15 OFFSET(15) NUMBITS(3) [
             Input = 0b000,
             Output = 0b001,
-            AltFunc0 = 0b100  // PL011 UART RX
-
+            AltFunc0 = 0b100, // PL011 UART RX
+            AltFunc5 = 0b010  // Mini UART RX
         ],
 
         /// Pin 14
         FSEL14 OFFSET(12) NUMBITS(3) [
             Input = 0b000,
             Output = 0b001,
-            AltFunc0 = 0b100  // PL011 UART TX
+            AltFunc0 = 0b100, // PL011 UART TX
+            AltFunc5 = 0b010  // Mini UART TX
         ]
     ],
 
@@ -98,12 +101,34 @@ register_bitfields! {
 register_structs! {
     #[allow(non_snake_case)]
     RegisterBlock {
-        (0x00 => _reserved1),
+        (0x00 => GPFSEL0: ReadWrite<u32>),
         (0x04 => GPFSEL1: ReadWrite<u32, GPFSEL1::Register>),
-        (0x08 => _reserved2),
+        (0x08 => GPFSEL2: ReadWrite<u32>),
+        (0x0C => GPFSEL3: ReadWrite<u32>),
+        (0x10 => GPFSEL4: ReadWrite<u32>),
+        (0x14 => GPFSEL5: ReadWrite<u32>),
+        (0x18 => _reserved2),
+        (0x1C => GPSET0: WriteOnly<u32>),
+        (0x20 => GPSET1: WriteOnly<u32>),
+        (0x24 => _reserved3),
+        (0x28 => GPCLR0: WriteOnly<u32>),
+        (0x2C => GPCLR1: WriteOnly<u32>),
+        (0x30 => _reserved4),
+        (0x34 => GPLEV0: ReadOnly<u32>),
+        (0x38 => GPLEV1: ReadOnly<u32>),
+        (0x3C => _reserved5),
+        (0x40 => GPEDS0: ReadWrite<u32>),
+        (0x44 => GPEDS1: ReadWrite<u32>),
+        (0x48 => _reserved6),
+        (0x4C => GPREN0: ReadWrite<u32>),
+        (0x50 => GPREN1: ReadWrite<u32>),
+        (0x54 => _reserved7),
+        (0x58 => GPFEN0: ReadWrite<u32>),
+        (0x5C => GPFEN1: ReadWrite<u32>),
+        (0x60 => _reserved8),
         (0x94 => GPPUD: ReadWrite<u32, GPPUD::Register>),
         (0x98 => GPPUDCLK0: ReadWrite<u32, GPPUDCLK0::Register>),
-        (0x9C => _reserved3),
+        (0x9C => _reserved9),
         (0xE4 => GPIO_PUP_PDN_CNTRL_REG0: ReadWrite<u32, GPIO_PUP_PDN_CNTRL_REG0::Register>),
         (0xE8 => @END),
     }
@@ -114,6 +139,9 @@ type Registers = MMIODerefWrapper<RegisterBlock>;
 
 struct GPIOInner {
     registers: Registers,
+
+    /// The last level written to each pin, since GPSETn/GPCLRn can not be read back.
+    output_shadow: u64,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -122,14 +150,57 @@ struct GPIOInner {
 
 /// Representation of the GPIO HW.
 pub struct GPIO {
-    inner: IRQSafeNullLock<GPIOInner>,
+    inner: Spinlock<GPIOInner>,
+}
+
+/// Type state of a pin that is configured as input.
+pub struct Input;
+
+/// Type state of a pin that is configured as output.
+pub struct Output;
+
+/// A single GPIO pin whose configuration is tracked in its type.
+///
+/// Only pins configured as output can be driven. The compiler rejects e.g. the following, because
+/// `Pin<Input>` has no `set_high()`. Doctests don't run on this target, so this is not checked
+/// automatically:
+///
+/// ```ignore
+/// let button = unsafe { libkernel::bsp::driver::gpio() }.into_input(16);
+///
+/// button.set_high();
+/// ```
+pub struct Pin<MODE> {
+    gpio: &'static GPIO,
+    pin: u32,
+    _mode: PhantomData<MODE>,
 }
 
 //--------------------------------------------------------------------------------------------------
 // Private Code
 //--------------------------------------------------------------------------------------------------
 
+/// Decide the level of a debounced pin from `num_high` high readings out of `samples`.
+///
+/// Ties resolve to `last`, the level of the latest sample.
+fn debounce_vote(num_high: u32, samples: u32, last: bool) -> bool {
+    match num_high.cmp(&(samples - num_high)) {
+        Ordering::Greater => true,
+        Ordering::Less => false,
+        Ordering::Equal => last,
+    }
+}
+
 impl GPIOInner {
+    /// Function select value for a GPIO input.
+    const FSEL_INPUT: u32 = 0b000;
+
+    /// Function select value for a GPIO output.
+    const FSEL_OUTPUT: u32 = 0b001;
+
+    /// Highest GPIO pin number.
+    const MAX_PIN: u32 = 53;
+
     /// Create an instance.
     ///
     /// # Safety
@@ -138,15 +209,13 @@ impl GPIOInner {
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
             registers: Registers::new(mmio_start_addr),
+            output_shadow: 0,
         }
     }
 
     /// Disable pull-up/down on pins 14 and 15.
     #[cfg(feature = "bsp_rpi3")]
     fn disable_pud_14_15_bcm2837(&mut self) {
-        use crate::time;
-        use core::time::Duration;
-
         // The Linux 2837 GPIO driver waits 1 µs between the steps.
         const DELAY: Duration = Duration::from_micros(1);
 
@@ -171,6 +240,164 @@ impl GPIOInner {
         );
     }
 
+    /// Program the 3-bit function select field of `pin`.
+    ///
+    /// Each GPFSEL register holds the fields of ten pins.
+    fn select_function(&mut self, pin: u32, function: u32) {
+        assert!(pin <= Self::MAX_PIN);
+
+        let shift = (pin % 10) * 3;
+        let update = |val: u32| (val & !(0b111 << shift)) | (function << shift);
+        let regs = &self.registers;
+
+        match pin / 10 {
+            0 => regs.GPFSEL0.set(update(regs.GPFSEL0.get())),
+            1 => regs.GPFSEL1.set(update(regs.GPFSEL1.get())),
+            2 => regs.GPFSEL2.set(update(regs.GPFSEL2.get())),
+            3 => regs.GPFSEL3.set(update(regs.GPFSEL3.get())),
+            4 => regs.GPFSEL4.set(update(regs.GPFSEL4.get())),
+            5 => regs.GPFSEL5.set(update(regs.GPFSEL5.get())),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Select alternate function `alt` on `pin`.
+    ///
+    /// The function select encoding of the alternate functions is not contiguous. See the table in
+    /// the peripherals datasheet for what each pin provides.
+    pub fn map_pin_alt(&mut self, pin: u32, alt: u32) {
+        const FSEL_ALT: [u32; 6] = [0b100, 0b101, 0b110, 0b111, 0b011, 0b010];
+
+        assert!(alt < FSEL_ALT.len() as u32);
+        self.select_function(pin, FSEL_ALT[alt as usize]);
+    }
+
+    /// Configure `pin` as input.
+    pub fn map_pin_input(&mut self, pin: u32) {
+        self.select_function(pin, Self::FSEL_INPUT);
+    }
+
+    /// Configure `pin` as output.
+    pub fn map_pin_output(&mut self, pin: u32) {
+        self.select_function(pin, Self::FSEL_OUTPUT);
+    }
+
+    /// Drive `pin` high.
+    pub fn turn_pin_on(&mut self, pin: u32) {
+        assert!(pin <= Self::MAX_PIN);
+
+        // Write-1-to-set. Zero bits have no effect, so no read-modify-write is needed.
+        match pin {
+            0..=31 => self.registers.GPSET0.set(1 << pin),
+            _ => self.registers.GPSET1.set(1 << (pin - 32)),
+        }
+
+        self.output_shadow |= 1 << pin;
+    }
+
+    /// Drive `pin` low.
+    pub fn turn_pin_off(&mut self, pin: u32) {
+        assert!(pin <= Self::MAX_PIN);
+
+        // Write-1-to-clear. Zero bits have no effect, so no read-modify-write is needed.
+        match pin {
+            0..=31 => self.registers.GPCLR0.set(1 << pin),
+            _ => self.registers.GPCLR1.set(1 << (pin - 32)),
+        }
+
+        self.output_shadow &= !(1 << pin);
+    }
+
+    /// Drive all pins of `bank` high whose bit is set in `mask`, with a single MMIO write.
+    fn set_pins(&mut self, bank: u32, mask: u32) {
+        match bank {
+            0 => self.registers.GPSET0.set(mask),
+            _ => self.registers.GPSET1.set(mask),
+        }
+
+        self.output_shadow |= (mask as u64) << (bank * 32);
+    }
+
+    /// Drive all pins of `bank` low whose bit is set in `mask`, with a single MMIO write.
+    fn clear_pins(&mut self, bank: u32, mask: u32) {
+        match bank {
+            0 => self.registers.GPCLR0.set(mask),
+            _ => self.registers.GPCLR1.set(mask),
+        }
+
+        self.output_shadow &= !((mask as u64) << (bank * 32));
+    }
+
+    /// Drive `pin` to the opposite of the level last written to it.
+    pub fn toggle_pin(&mut self, pin: u32) {
+        assert!(pin <= Self::MAX_PIN);
+
+        if (self.output_shadow & (1 << pin)) != 0 {
+            self.turn_pin_off(pin);
+        } else {
+            self.turn_pin_on(pin);
+        }
+    }
+
+    /// Return true if `pin` is high.
+    pub fn read_pin(&self, pin: u32) -> bool {
+        assert!(pin <= Self::MAX_PIN);
+
+        let level = match pin {
+            0..=31 => self.registers.GPLEV0.get() >> pin,
+            _ => self.registers.GPLEV1.get() >> (pin - 32),
+        };
+
+        (level & 1) != 0
+    }
+
+    /// Enable rising edge detection for `pin`.
+    pub fn enable_rising_edge(&mut self, pin: u32) {
+        assert!(pin <= Self::MAX_PIN);
+
+        let (reg, bit) = match pin {
+            0..=31 => (&self.registers.GPREN0, pin),
+            _ => (&self.registers.GPREN1, pin - 32),
+        };
+        reg.set(reg.get() | (1 << bit));
+    }
+
+    /// Enable falling edge detection for `pin`.
+    pub fn enable_falling_edge(&mut self, pin: u32) {
+        assert!(pin <= Self::MAX_PIN);
+
+        let (reg, bit) = match pin {
+            0..=31 => (&self.registers.GPFEN0, pin),
+            _ => (&self.registers.GPFEN1, pin - 32),
+        };
+        reg.set(reg.get() | (1 << bit));
+    }
+
+    /// Return true if an enabled edge was detected on `pin`.
+    ///
+    /// The event status is latched. It stays set until cleared with `clear_event()`.
+    pub fn event_detected(&self, pin: u32) -> bool {
+        assert!(pin <= Self::MAX_PIN);
+
+        let status = match pin {
+            0..=31 => self.registers.GPEDS0.get() >> pin,
+            _ => self.registers.GPEDS1.get() >> (pin - 32),
+        };
+
+        (status & 1) != 0
+    }
+
+    /// Clear the latched event status of `pin`.
+    pub fn clear_event(&mut self, pin: u32) {
+        assert!(pin <= Self::MAX_PIN);
+
+        // Write-1-to-clear. A read-modify-write would clear the events of all other pins, too.
+        match pin {
+            0..=31 => self.registers.GPEDS0.set(1 << pin),
+            _ => self.registers.GPEDS1.set(1 << (pin - 32)),
+        }
+    }
+
     /// Map PL011 UART as standard output.
     ///
     /// TX to pin 14
@@ -188,6 +415,37 @@ impl GPIOInner {
         #[cfg(feature = "bsp_rpi4")]
         self.disable_pud_14_15_bcm2711();
     }
+
+    /// Map SPI0 to its pins.
+    ///
+    /// CE1 to pin 7
+    /// CE0 to pin 8
+    /// MISO to pin 9
+    /// MOSI to pin 10
+    /// SCLK to pin 11
+    pub fn map_spi0(&mut self) {
+        for pin in 7..=11 {
+            self.map_pin_alt(pin, 0);
+        }
+    }
+
+    /// Map the mini UART as standard output.
+    ///
+    /// TX to pin 14
+    /// RX to pin 15
+    pub fn map_mini_uart(&mut self) {
+        // Select the mini UART on pins 14 and 15.
+        self.registers
+            .GPFSEL1
+            .modify(GPFSEL1::FSEL15::AltFunc5 + GPFSEL1::FSEL14::AltFunc5);
+
+        // Disable pull-up/down on pins 14 and 15.
+        #[cfg(feature = "bsp_rpi3")]
+        self.disable_pud_14_15_bcm2837();
+
+        #[cfg(feature = "bsp_rpi4")]
+        self.disable_pud_14_15_bcm2711();
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -204,7 +462,7 @@ impl GPIO {
     /// - The user must ensure to provide a correct MMIO start address.
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
-            inner: IRQSafeNullLock::new(GPIOInner::new(mmio_start_addr)),
+            inner: Spinlock::new(GPIOInner::new(mmio_start_addr)),
         }
     }
 
@@ -212,6 +470,194 @@ impl GPIO {
     pub fn map_pl011_uart(&self) {
         self.inner.lock(|inner| inner.map_pl011_uart())
     }
+
+    /// Concurrency safe version of `GPIOInner.map_mini_uart()`
+    pub fn map_mini_uart(&self) {
+        self.inner.lock(|inner| inner.map_mini_uart())
+    }
+
+    /// Concurrency safe version of `GPIOInner.map_spi0()`
+    pub fn map_spi0(&self) {
+        self.inner.lock(|inner| inner.map_spi0())
+    }
+
+    /// Concurrency safe version of `GPIOInner.map_pin_alt()`
+    pub fn map_pin_alt(&self, pin: u32, alt: u32) {
+        self.inner.lock(|inner| inner.map_pin_alt(pin, alt))
+    }
+
+    /// Concurrency safe version of `GPIOInner.map_pin_input()`
+    pub fn map_pin_input(&self, pin: u32) {
+        self.inner.lock(|inner| inner.map_pin_input(pin))
+    }
+
+    /// Concurrency safe version of `GPIOInner.read_pin()`
+    pub fn read_pin(&self, pin: u32) -> bool {
+        self.inner.lock(|inner| inner.read_pin(pin))
+    }
+
+    /// Drive pins 0-31 high according to `mask`, where bit N corresponds to pin N.
+    ///
+    /// All pins change with the same MMIO write. Zero bits leave the respective pin alone.
+    pub fn set_pins(&self, mask: u32) {
+        self.inner.lock(|inner| inner.set_pins(0, mask))
+    }
+
+    /// Drive pins 0-31 low according to `mask`, where bit N corresponds to pin N.
+    ///
+    /// All pins change with the same MMIO write. Zero bits leave the respective pin alone.
+    pub fn clear_pins(&self, mask: u32) {
+        self.inner.lock(|inner| inner.clear_pins(0, mask))
+    }
+
+    /// Drive pins 32-53 high according to `mask`, where bit N corresponds to pin 32 + N.
+    ///
+    /// Bits 22-31 do not correspond to a pin and are ignored by the HW.
+    pub fn set_pins_hi(&self, mask: u32) {
+        self.inner.lock(|inner| inner.set_pins(1, mask))
+    }
+
+    /// Drive pins 32-53 low according to `mask`, where bit N corresponds to pin 32 + N.
+    ///
+    /// Bits 22-31 do not correspond to a pin and are ignored by the HW.
+    pub fn clear_pins_hi(&self, mask: u32) {
+        self.inner.lock(|inner| inner.clear_pins(1, mask))
+    }
+
+    /// Concurrency safe version of `GPIOInner.toggle_pin()`
+    pub fn toggle_pin(&self, pin: u32) {
+        self.inner.lock(|inner| inner.toggle_pin(pin))
+    }
+
+    /// Concurrency safe version of `GPIOInner.enable_rising_edge()`
+    pub fn enable_rising_edge(&self, pin: u32) {
+        self.inner.lock(|inner| inner.enable_rising_edge(pin))
+    }
+
+    /// Concurrency safe version of `GPIOInner.enable_falling_edge()`
+    pub fn enable_falling_edge(&self, pin: u32) {
+        self.inner.lock(|inner| inner.enable_falling_edge(pin))
+    }
+
+    /// Concurrency safe version of `GPIOInner.event_detected()`
+    ///
+    /// Reading the status does not clear it. Call `clear_event()` to re-arm detection.
+    pub fn event_detected(&self, pin: u32) -> bool {
+        self.inner.lock(|inner| inner.event_detected(pin))
+    }
+
+    /// Concurrency safe version of `GPIOInner.clear_event()`
+    pub fn clear_event(&self, pin: u32) {
+        self.inner.lock(|inner| inner.clear_event(pin))
+    }
+
+    /// Read `pin` `samples` times, spinning for `gap` in between, and return the majority level.
+    ///
+    /// Meant for mechanical buttons that bounce. The lock is not held while spinning. With
+    /// `samples == 0`, a single instantaneous reading is returned.
+    pub fn read_pin_debounced(&self, pin: u32, samples: u32, gap: Duration) -> bool {
+        if samples == 0 {
+            return self.read_pin(pin);
+        }
+
+        let mut num_high = 0;
+        let mut last = false;
+        for i in 0..samples {
+            if i > 0 {
+                time::time_manager().spin_for(gap);
+            }
+
+            last = self.read_pin(pin);
+            if last {
+                num_high += 1;
+            }
+        }
+
+        debounce_vote(num_high, samples, last)
+    }
+
+    /// Concurrency safe version of `GPIOInner.map_pin_output()`
+    pub fn set_output_pin(&self, pin: u32) {
+        self.inner.lock(|inner| inner.map_pin_output(pin))
+    }
+
+    /// Concurrency safe version of `GPIOInner.turn_pin_on()`
+    pub fn set_pin_on(&self, pin: u32) {
+        self.inner.lock(|inner| inner.turn_pin_on(pin))
+    }
+
+    /// Concurrency safe version of `GPIOInner.turn_pin_off()`
+    pub fn set_pin_off(&self, pin: u32) {
+        self.inner.lock(|inner| inner.turn_pin_off(pin))
+    }
+
+    /// Configure `pin` as output and return a handle that can drive it.
+    pub fn into_output(&'static self, pin: u32) -> Pin<Output> {
+        self.set_output_pin(pin);
+
+        Pin::new(self, pin)
+    }
+
+    /// Configure `pin` as input and return a handle that can read it.
+    pub fn into_input(&'static self, pin: u32) -> Pin<Input> {
+        self.map_pin_input(pin);
+
+        Pin::new(self, pin)
+    }
+}
+
+impl<MODE> Pin<MODE> {
+    const fn new(gpio: &'static GPIO, pin: u32) -> Self {
+        Self {
+            gpio,
+            pin,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Return the pin number.
+    pub fn pin(&self) -> u32 {
+        self.pin
+    }
+}
+
+impl Pin<Output> {
+    /// Drive the pin high.
+    pub fn set_high(&self) {
+        self.gpio.set_pin_on(self.pin)
+    }
+
+    /// Drive the pin low.
+    pub fn set_low(&self) {
+        self.gpio.set_pin_off(self.pin)
+    }
+
+    /// Drive the pin to the opposite of its current level.
+    pub fn toggle(&self) {
+        self.gpio.toggle_pin(self.pin)
+    }
+
+    /// Reconfigure the pin as input.
+    pub fn into_input(self) -> Pin<Input> {
+        self.gpio.into_input(self.pin)
+    }
+}
+
+impl Pin<Input> {
+    /// Return true if the pin is high.
+    pub fn is_high(&self) -> bool {
+        self.gpio.read_pin(self.pin)
+    }
+
+    /// Return true if the pin is low.
+    pub fn is_low(&self) -> bool {
+        !self.is_high()
+    }
+
+    /// Reconfigure the pin as output.
+    pub fn into_output(self) -> Pin<Output> {
+        self.gpio.into_output(self.pin)
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -225,4 +671,127 @@ impl driver::interface::DeviceDriver for GPIO {
     fn compatible(&self) -> &'static str {
         Self::COMPATIBLE
     }
+
+    fn probe(&self) -> Result<(), &'static str> {
+        // Bits 31:30 of every GPFSEL register are reserved and read as zero.
+        const GPFSEL_RESERVED_MASK: u32 = 0b11 << 30;
+
+        self.inner.lock(|inner| {
+            if (inner.registers.GPFSEL1.get() & GPFSEL_RESERVED_MASK) != 0 {
+                return Err("Reserved GPFSEL1 bits not reading as zero");
+            }
+
+            Ok(())
+        })
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::{addr_of_mut, read_volatile, write_volatile};
+    use test_macros::kernel_test;
+
+    const GPSET0_INDEX: usize = 0x1C / 4;
+    const GPCLR0_INDEX: usize = 0x28 / 4;
+    const GPEDS0_INDEX: usize = 0x40 / 4;
+    const GPREN0_INDEX: usize = 0x4C / 4;
+
+    /// Plain memory standing in for the MMIO registers.
+    static mut MOCK_REGISTERS: [u32; 0xE8 / 4] = [0; 0xE8 / 4];
+
+    /// Event detection must set the enable bit and clear the status with a single write-1.
+    #[kernel_test]
+    fn edge_detect_status_clear_round_trip() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = GPIOInner::new(Address::new(mock as usize));
+
+            write_volatile(mock.add(GPREN0_INDEX), 1 << 5);
+            inner.enable_rising_edge(21);
+            assert_eq!(read_volatile(mock.add(GPREN0_INDEX)), (1 << 21) | (1 << 5));
+
+            // Pretend that the HW latched events on pins 21 and 5.
+            write_volatile(mock.add(GPEDS0_INDEX), (1 << 21) | (1 << 5));
+            assert!(inner.event_detected(21));
+            assert!(!inner.event_detected(20));
+
+            // Only bit 21 must be written. On real HW, this clears the event on pin 21 and leaves
+            // the one on pin 5 alone.
+            inner.clear_event(21);
+            assert_eq!(read_volatile(mock.add(GPEDS0_INDEX)), 1 << 21);
+        }
+    }
+
+    /// Pins 0-9 live in GPFSEL0, and only the selected field must change.
+    #[kernel_test]
+    fn select_function_covers_gpfsel0() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = GPIOInner::new(Address::new(mock as usize));
+
+            write_volatile(mock, 0b111 << 27);
+            inner.map_pin_output(4);
+            assert_eq!(read_volatile(mock), (0b111 << 27) | (0b001 << 12));
+
+            inner.map_pin_input(4);
+            assert_eq!(read_volatile(mock), 0b111 << 27);
+        }
+    }
+
+    /// SPI0 must be selected as ALT0 on pins 7-11, which spans GPFSEL0 and GPFSEL1.
+    #[kernel_test]
+    fn map_spi0_selects_alt0() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = GPIOInner::new(Address::new(mock as usize));
+
+            write_volatile(mock, 0);
+            write_volatile(mock.add(1), 0);
+            inner.map_spi0();
+            assert_eq!(
+                read_volatile(mock),
+                (0b100 << 27) | (0b100 << 24) | (0b100 << 21)
+            );
+            assert_eq!(read_volatile(mock.add(1)), (0b100 << 3) | 0b100);
+
+            write_volatile(mock.add(2), 0);
+            inner.map_pin_alt(20, 5);
+            assert_eq!(read_volatile(mock.add(2)), 0b010);
+        }
+    }
+
+    /// Majority wins, ties go to the latest sample.
+    #[kernel_test]
+    fn debounce_vote_works() {
+        assert!(debounce_vote(3, 5, false));
+        assert!(!debounce_vote(2, 5, true));
+        assert!(debounce_vote(2, 4, true));
+        assert!(!debounce_vote(2, 4, false));
+        assert!(debounce_vote(1, 1, true));
+    }
+
+    /// Toggling twice must restore the initial output level.
+    #[kernel_test]
+    fn toggle_pin_round_trip() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = GPIOInner::new(Address::new(mock as usize));
+
+            inner.turn_pin_on(47);
+            let initial = inner.output_shadow;
+
+            inner.toggle_pin(21);
+            assert_eq!(inner.output_shadow, initial | (1 << 21));
+            assert_eq!(read_volatile(mock.add(GPSET0_INDEX)), 1 << 21);
+
+            inner.toggle_pin(21);
+            assert_eq!(inner.output_shadow, initial);
+            assert_eq!(read_volatile(mock.add(GPCLR0_INDEX)), 1 << 21);
+        }
+    }
 }
@@ -168,6 +168,13 @@ impl exception::asynchronous::interface::IRQManager for InterruptController {
         self.periph.handle_pending_irqs(ic)
     }
 
+    fn count(&self, irq: &Self::IRQNumberType) -> u64 {
+        match irq {
+            IRQNumber::Local(lirq) => self.local.count(lirq),
+            IRQNumber::Peripheral(pirq) => self.periph.count(pirq),
+        }
+    }
+
     fn print_handler(&self) {
         self.local.print_handler();
         self.periph.print_handler();
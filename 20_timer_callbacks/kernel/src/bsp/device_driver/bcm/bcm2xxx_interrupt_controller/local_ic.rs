@@ -14,7 +14,7 @@ use crate::{
     exception,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::{IRQSafeNullLock, InitStateLock},
+    synchronization::Spinlock,
 };
 use alloc::vec::Vec;
 use tock_registers::{
@@ -60,13 +60,17 @@ type HandlerTable = Vec<Option<exception::asynchronous::IRQHandlerDescriptor<Loc
 /// Representation of the peripheral interrupt controller.
 pub struct LocalIC {
     /// Access to write registers is guarded with a lock.
-    wo_registers: IRQSafeNullLock<WriteOnlyRegisters>,
+    wo_registers: Spinlock<WriteOnlyRegisters>,
 
     /// Register read access is unguarded.
     ro_registers: ReadOnlyRegisters,
 
-    /// Stores registered IRQ handlers. Writable only during kernel init. RO afterwards.
-    handler_table: InitStateLock<HandlerTable>,
+    /// Stores registered IRQ handlers. Writable at runtime, so that handlers can be registered
+    /// after kernel init, too.
+    handler_table: Spinlock<HandlerTable>,
+
+    /// Number of dispatches per IRQ.
+    irq_counts: exception::asynchronous::IRQCounts,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -84,16 +88,18 @@ impl LocalIC {
     /// - The user must ensure to provide a correct MMIO start address.
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
-            wo_registers: IRQSafeNullLock::new(WriteOnlyRegisters::new(mmio_start_addr)),
+            wo_registers: Spinlock::new(WriteOnlyRegisters::new(mmio_start_addr)),
             ro_registers: ReadOnlyRegisters::new(mmio_start_addr),
-            handler_table: InitStateLock::new(Vec::new()),
+            handler_table: Spinlock::new(Vec::new()),
+            irq_counts: exception::asynchronous::IRQCounts::new(),
         }
     }
 
     /// Called by the kernel to bring up the device.
     pub fn init(&self) {
         self.handler_table
-            .write(|table| table.resize(LocalIRQ::MAX_INCLUSIVE + 1, None));
+            .lock(|table| table.resize(LocalIRQ::MAX_INCLUSIVE + 1, None));
+        self.irq_counts.init(LocalIRQ::MAX_INCLUSIVE);
     }
 
     /// Query the list of pending IRQs.
@@ -108,7 +114,7 @@ impl LocalIC {
 //------------------------------------------------------------------------------
 // OS Interface Code
 //------------------------------------------------------------------------------
-use synchronization::interface::{Mutex, ReadWriteEx};
+use synchronization::interface::Mutex;
 
 impl exception::asynchronous::interface::IRQManager for LocalIC {
     type IRQNumberType = LocalIRQ;
@@ -117,7 +123,7 @@ impl exception::asynchronous::interface::IRQManager for LocalIC {
         &self,
         irq_handler_descriptor: exception::asynchronous::IRQHandlerDescriptor<Self::IRQNumberType>,
     ) -> Result<(), &'static str> {
-        self.handler_table.write(|table| {
+        self.handler_table.lock(|table| {
             let irq_number = irq_handler_descriptor.number().get();
 
             if table[irq_number].is_some() {
@@ -144,17 +150,22 @@ impl exception::asynchronous::interface::IRQManager for LocalIC {
         &'irq_context self,
         _ic: &exception::asynchronous::IRQContext<'irq_context>,
     ) {
-        self.handler_table.read(|table| {
-            for irq_number in self.pending_irqs() {
-                match table[irq_number] {
-                    None => panic!("No handler registered for IRQ {}", irq_number),
-                    Some(descriptor) => {
-                        // Call the IRQ handler. Panics on failure.
-                        descriptor.handler().handle().expect("Error handling IRQ");
-                    }
+        for irq_number in self.pending_irqs() {
+            // Copy the descriptor out, so that the handler runs without the table being locked.
+            match self.handler_table.lock(|table| table[irq_number]) {
+                None => panic!("No handler registered for IRQ {}", irq_number),
+                Some(descriptor) => {
+                    self.irq_counts.increment(irq_number);
+
+                    // Call the IRQ handler. Panics on failure.
+                    descriptor.handler().handle().expect("Error handling IRQ");
                 }
             }
-        })
+        }
+    }
+
+    fn count(&self, irq: &Self::IRQNumberType) -> u64 {
+        self.irq_counts.get(irq.get())
     }
 
     fn print_handler(&self) {
@@ -162,10 +173,15 @@ impl exception::asynchronous::interface::IRQManager for LocalIC {
 
         info!("      Local handler:");
 
-        self.handler_table.read(|table| {
+        self.handler_table.lock(|table| {
             for (i, opt) in table.iter().enumerate() {
                 if let Some(handler) = opt {
-                    info!("            {: >3}. {}", i, handler.name());
+                    info!(
+                        "            IRQ {: >3} ({}): {} handled",
+                        i,
+                        handler.name(),
+                        self.irq_counts.get(i)
+                    );
                 }
             }
         });
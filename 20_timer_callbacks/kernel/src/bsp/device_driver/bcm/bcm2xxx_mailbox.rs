@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! VideoCore Mailbox Driver.
+//!
+//! Only the property tags channel is supported. Requests are built in a `PropertyBuffer`, which
+//! the VideoCore reads and overwrites with its response.
+//!
+//! # Resources
+//!
+//! - <https://github.com/raspberrypi/firmware/wiki/Mailbox-property-interface>
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    cpu, driver,
+    exception::asynchronous::IRQNumber,
+    memory::{self, Address, Virtual},
+    synchronization,
+    synchronization::Spinlock,
+};
+use tock_registers::{
+    interfaces::{Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, WriteOnly},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+register_bitfields! {
+    u32,
+
+    /// Mailbox Status
+    STATUS [
+        /// The mailbox can not accept more messages.
+        FULL OFFSET(31) NUMBITS(1) [],
+
+        /// The mailbox holds no messages.
+        EMPTY OFFSET(30) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        // Mailbox 0, VideoCore to ARM.
+        (0x00 => READ: ReadOnly<u32>),
+        (0x04 => _reserved1),
+        (0x18 => STATUS0: ReadOnly<u32, STATUS::Register>),
+        (0x1C => _reserved2),
+        // Mailbox 1, ARM to VideoCore.
+        (0x20 => WRITE: WriteOnly<u32>),
+        (0x24 => _reserved3),
+        (0x38 => STATUS1: ReadOnly<u32, STATUS::Register>),
+        (0x3C => _reserved4),
+        (0x40 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct MailboxInner {
+    registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A message for the property tags channel.
+///
+/// Word 0 holds the size in bytes and word 1 the request/response code, both of which are filled
+/// in by `Mailbox::call()`. The tags follow from word 2 on and must be terminated by a zero word.
+///
+/// The mailbox needs 16 byte alignment. Cache line alignment is used, so that cache maintenance on
+/// the buffer can not touch neighbouring data.
+#[repr(C, align(64))]
+pub struct PropertyBuffer<const WORDS: usize>(pub [u32; WORDS]);
+
+/// Representation of the VideoCore mailbox.
+pub struct Mailbox {
+    inner: Spinlock<MailboxInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl MailboxInner {
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+        }
+    }
+
+    /// Send `message` on `channel` and wait for the VideoCore's answer to it.
+    fn send_and_wait(&mut self, channel: u32, message: u32) {
+        while self.registers.STATUS1.is_set(STATUS::FULL) {
+            cpu::nop();
+        }
+        self.registers.WRITE.set(message | channel);
+
+        loop {
+            while self.registers.STATUS0.is_set(STATUS::EMPTY) {
+                cpu::nop();
+            }
+
+            // Answers to other messages are dropped. This driver is the only user.
+            if self.registers.READ.get() == (message | channel) {
+                return;
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<const WORDS: usize> PropertyBuffer<WORDS> {
+    /// The tag that terminates the list of tags.
+    pub const TAG_END: u32 = 0;
+
+    /// Code in word 1 for a request.
+    const CODE_REQUEST: u32 = 0;
+
+    /// Code in word 1 if the VideoCore processed the request successfully.
+    const CODE_RESPONSE_SUCCESS: u32 = 0x8000_0000;
+
+    /// Create an instance with all words set to zero.
+    pub const fn new() -> Self {
+        Self([0; WORDS])
+    }
+}
+
+impl Mailbox {
+    pub const COMPATIBLE: &'static str = "BCM Mailbox";
+
+    /// The property tags channel, ARM to VideoCore.
+    const CHANNEL_PROPERTY: u32 = 8;
+
+    /// The VideoCore accesses memory through the uncached alias of the ARM physical address.
+    const VC_UNCACHED_ALIAS: u32 = 0xC000_0000;
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: Spinlock::new(MailboxInner::new(mmio_start_addr)),
+        }
+    }
+
+    /// Convert an address handed out by the VideoCore to an ARM physical address.
+    pub const fn vc_to_arm_addr(vc_addr: u32) -> usize {
+        (vc_addr & !Self::VC_UNCACHED_ALIAS) as usize
+    }
+
+    /// Send the tags in `buf` on the property channel and wait for the response.
+    ///
+    /// On success, the response values have been written into the tags of `buf`.
+    pub fn call<const WORDS: usize>(
+        &self,
+        buf: &mut PropertyBuffer<WORDS>,
+    ) -> Result<(), &'static str> {
+        let virt_addr = Address::<Virtual>::new(buf.0.as_ptr() as usize);
+        let size = core::mem::size_of_val(&buf.0);
+
+        let phys_addr = match memory::mmu::virt_to_phys(virt_addr) {
+            None => return Err("Property buffer is not mapped"),
+            Some(x) => x.as_usize(),
+        };
+        if phys_addr > (u32::MAX & !Self::VC_UNCACHED_ALIAS) as usize {
+            return Err("Property buffer is out of reach of the VideoCore");
+        }
+
+        buf.0[0] = size as u32;
+        buf.0[1] = PropertyBuffer::<WORDS>::CODE_REQUEST;
+
+        // The VideoCore does not snoop the ARM caches.
+        memory::cache::clean_and_invalidate_range(virt_addr, size);
+
+        self.inner.lock(|inner| {
+            inner.send_and_wait(
+                Self::CHANNEL_PROPERTY,
+                phys_addr as u32 | Self::VC_UNCACHED_ALIAS,
+            )
+        });
+
+        memory::cache::invalidate_range(virt_addr, size);
+
+        if buf.0[1] != PropertyBuffer::<WORDS>::CODE_RESPONSE_SUCCESS {
+            return Err("VideoCore rejected the property request");
+        }
+
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Mailbox {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+}
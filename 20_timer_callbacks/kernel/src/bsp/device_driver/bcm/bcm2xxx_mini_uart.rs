@@ -0,0 +1,384 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Mini UART Driver.
+//!
+//! The mini UART is part of the AUX peripheral block. Its baud rate is derived from the VPU core
+//! clock, so the core clock must be fixed, e.g. with `core_freq` or `enable_uart=1` in config.txt.
+//! The driver polls and does not use IRQs.
+//!
+//! # Resources
+//!
+//! - <https://github.com/raspberrypi/documentation/files/1888662/BCM2837-ARM-Peripherals.-.Revised.-.V2-1.pdf>
+//! - <https://datasheets.raspberrypi.org/bcm2711/bcm2711-peripherals.pdf>
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    console, cpu, driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::Spinlock,
+    time,
+};
+use core::{fmt, time::Duration};
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, ReadWrite, WriteOnly},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+// Mini UART registers.
+//
+// Descriptions taken from
+// - https://github.com/raspberrypi/documentation/files/1888662/BCM2837-ARM-Peripherals.-.Revised.-.V2-1.pdf
+register_bitfields! {
+    u32,
+
+    /// Auxiliary enables
+    AUX_ENABLES [
+        /// Mini UART enable. Also gives access to the mini UART registers.
+        MINI_UART OFFSET(0) NUMBITS(1) [
+            Disabled = 0,
+            Enabled = 1
+        ]
+    ],
+
+    /// Mini UART Interrupt Identify
+    AUX_MU_IIR [
+        /// On write, clear the FIFOs.
+        FIFO_CLEAR OFFSET(1) NUMBITS(2) [
+            Rx = 0b01,
+            Tx = 0b10,
+            Both = 0b11
+        ]
+    ],
+
+    /// Mini UART Line Control
+    AUX_MU_LCR [
+        /// Data size.
+        DATA_SIZE OFFSET(0) NUMBITS(2) [
+            SevenBit = 0b00,
+            EightBit = 0b11
+        ]
+    ],
+
+    /// Mini UART Line Status
+    AUX_MU_LSR [
+        /// The TX FIFO is empty and the transmitter is idle.
+        TX_IDLE OFFSET(6) NUMBITS(1) [],
+
+        /// The TX FIFO can accept at least one character.
+        TX_EMPTY OFFSET(5) NUMBITS(1) [],
+
+        /// The RX FIFO holds at least one character.
+        DATA_READY OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Mini UART Extra Control
+    AUX_MU_CNTL [
+        /// Transmitter enable.
+        TX_ENABLE OFFSET(1) NUMBITS(1) [],
+
+        /// Receiver enable.
+        RX_ENABLE OFFSET(0) NUMBITS(1) []
+    ],
+
+    /// Mini UART Baudrate
+    AUX_MU_BAUD [
+        /// Baud rate = core clock / (8 * (BAUDRATE + 1)).
+        BAUDRATE OFFSET(0) NUMBITS(16) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x00 => _reserved1),
+        (0x04 => AUX_ENABLES: ReadWrite<u32, AUX_ENABLES::Register>),
+        (0x08 => _reserved2),
+        (0x40 => AUX_MU_IO: ReadWrite<u32>),
+        (0x44 => AUX_MU_IER: ReadWrite<u32>),
+        (0x48 => AUX_MU_IIR: WriteOnly<u32, AUX_MU_IIR::Register>),
+        (0x4C => AUX_MU_LCR: ReadWrite<u32, AUX_MU_LCR::Register>),
+        (0x50 => AUX_MU_MCR: ReadWrite<u32>),
+        (0x54 => AUX_MU_LSR: ReadOnly<u32, AUX_MU_LSR::Register>),
+        (0x58 => _reserved3),
+        (0x60 => AUX_MU_CNTL: ReadWrite<u32, AUX_MU_CNTL::Register>),
+        (0x64 => _reserved4),
+        (0x68 => AUX_MU_BAUD: ReadWrite<u32, AUX_MU_BAUD::Register>),
+        (0x6C => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+/// Time between two polls of the RX FIFO. Receiving one character takes ~87 µs at 115200 baud.
+const RX_POLL_INTERVAL: Duration = Duration::from_micros(20);
+
+struct MiniUartInner {
+    registers: Registers,
+    core_clock_hz: u32,
+    chars_written: usize,
+    chars_read: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the mini UART.
+pub struct MiniUart {
+    inner: Spinlock<MiniUartInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Compute the value of the baud rate register, rounded to the nearest divisor.
+const fn baud_divisor(core_clock_hz: u32, baud: u32) -> u32 {
+    let divider = 8 * baud;
+
+    ((core_clock_hz + (divider / 2)) / divider) - 1
+}
+
+impl MiniUartInner {
+    const BAUD: u32 = 115_200;
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>, core_clock_hz: u32) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            core_clock_hz,
+            chars_written: 0,
+            chars_read: 0,
+        }
+    }
+
+    /// Set up 8N1 at 115_200 baud.
+    pub fn init(&mut self) {
+        // As with the PL011, do not cut off characters that are still being sent.
+        self.flush();
+
+        self.registers
+            .AUX_ENABLES
+            .modify(AUX_ENABLES::MINI_UART::Enabled);
+
+        // Turn TX and RX off while configuring, and do not use IRQs or flow control.
+        self.registers.AUX_MU_CNTL.set(0);
+        self.registers.AUX_MU_IER.set(0);
+        self.registers.AUX_MU_MCR.set(0);
+
+        self.registers
+            .AUX_MU_LCR
+            .write(AUX_MU_LCR::DATA_SIZE::EightBit);
+        self.registers
+            .AUX_MU_IIR
+            .write(AUX_MU_IIR::FIFO_CLEAR::Both);
+        self.registers
+            .AUX_MU_BAUD
+            .write(AUX_MU_BAUD::BAUDRATE.val(baud_divisor(self.core_clock_hz, Self::BAUD)));
+
+        self.registers
+            .AUX_MU_CNTL
+            .write(AUX_MU_CNTL::TX_ENABLE::SET + AUX_MU_CNTL::RX_ENABLE::SET);
+    }
+
+    /// Send a character.
+    fn write_char(&mut self, c: char) {
+        while !self.registers.AUX_MU_LSR.is_set(AUX_MU_LSR::TX_EMPTY) {
+            cpu::nop();
+        }
+
+        self.registers.AUX_MU_IO.set(c as u32);
+
+        self.chars_written += 1;
+    }
+
+    /// Block execution until the last buffered character has been physically put on the TX wire.
+    fn flush(&self) {
+        // Skip if the mini UART is off. Its registers can not be accessed then.
+        if !self
+            .registers
+            .AUX_ENABLES
+            .matches_all(AUX_ENABLES::MINI_UART::Enabled)
+        {
+            return;
+        }
+
+        while !self.registers.AUX_MU_LSR.is_set(AUX_MU_LSR::TX_IDLE) {
+            cpu::nop();
+        }
+    }
+
+    /// Retrieve a character, if one is available.
+    fn read_char_converting(&mut self) -> Option<char> {
+        if !self.registers.AUX_MU_LSR.is_set(AUX_MU_LSR::DATA_READY) {
+            return None;
+        }
+
+        let mut ret = self.registers.AUX_MU_IO.get() as u8 as char;
+
+        // Convert carrige return to newline.
+        if ret == '\r' {
+            ret = '\n'
+        }
+
+        self.chars_read += 1;
+
+        Some(ret)
+    }
+}
+
+/// Implementing `core::fmt::Write` enables usage of the `format_args!` macros.
+impl fmt::Write for MiniUartInner {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.write_char(c);
+        }
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl MiniUart {
+    pub const COMPATIBLE: &'static str = "BCM Mini UART";
+
+    /// Create an instance.
+    ///
+    /// `core_clock_hz` is the VPU core clock, which the baud rate is derived from.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>, core_clock_hz: u32) -> Self {
+        Self {
+            inner: Spinlock::new(MiniUartInner::new(mmio_start_addr, core_clock_hz)),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for MiniUart {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.init());
+
+        Ok(())
+    }
+}
+
+impl console::interface::Write for MiniUart {
+    fn write_char(&self, c: char) {
+        self.inner.lock(|inner| inner.write_char(c));
+    }
+
+    fn write_array(&self, a: &[char]) {
+        self.inner.lock(|inner| {
+            for c in a {
+                inner.write_char(*c);
+            }
+        });
+    }
+
+    fn write_fmt(&self, args: core::fmt::Arguments) -> fmt::Result {
+        self.inner.lock(|inner| fmt::Write::write_fmt(inner, args))
+    }
+
+    fn flush(&self) {
+        self.inner.lock(|inner| inner.flush());
+    }
+
+    unsafe fn panic_takeover(&self, lock_timeout: Duration) {
+        self.inner.force_unlock(lock_timeout);
+    }
+
+    fn panic_flush(&self) {
+        // Only polls the status registers.
+        unsafe { self.inner.data_unlocked() }.flush()
+    }
+}
+
+impl console::interface::Read for MiniUart {
+    fn read_char_timeout(&self, timeout: Duration) -> Option<u8> {
+        // No deadline if it is not representable, e.g. for `Duration::MAX`.
+        let deadline = time::time_manager().uptime().checked_add(timeout);
+
+        // The lock is only taken for each poll, so that waiting neither holds off IRQs on this core
+        // nor printing on the others.
+        loop {
+            if let Some(c) = self.inner.lock(|inner| inner.read_char_converting()) {
+                return Some(c as u8);
+            }
+
+            match deadline {
+                Some(d) if time::time_manager().uptime() >= d => return None,
+                _ => time::time_manager().spin_for(RX_POLL_INTERVAL),
+            }
+        }
+    }
+
+    fn clear_rx(&self) {
+        while self
+            .inner
+            .lock(|inner| inner.read_char_converting())
+            .is_some()
+        {}
+    }
+}
+
+impl console::interface::Statistics for MiniUart {
+    fn chars_written(&self) -> usize {
+        self.inner.lock(|inner| inner.chars_written)
+    }
+
+    fn chars_read(&self) -> usize {
+        self.inner.lock(|inner| inner.chars_read)
+    }
+}
+
+impl console::interface::All for MiniUart {}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// The divisor must match the documented formula for common core clocks.
+    #[kernel_test]
+    fn baud_divisor_matches_core_clock() {
+        // 250 MHz / (8 * 115_200) = 271.27
+        assert_eq!(baud_divisor(250_000_000, 115_200), 270);
+
+        // 500 MHz / (8 * 115_200) = 542.53
+        assert_eq!(baud_divisor(500_000_000, 115_200), 542);
+    }
+}
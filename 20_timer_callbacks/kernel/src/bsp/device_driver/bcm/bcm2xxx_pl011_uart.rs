@@ -15,9 +15,11 @@ use crate::{
     exception::{self, asynchronous::IRQNumber},
     memory::{Address, Virtual},
     synchronization,
-    synchronization::IRQSafeNullLock,
+    synchronization::Spinlock,
+    time,
 };
-use core::fmt;
+use alloc::vec::Vec;
+use core::{fmt, time::Duration};
 use tock_registers::{
     interfaces::{Readable, Writeable},
     register_bitfields, register_structs,
@@ -214,16 +216,22 @@ register_structs! {
 /// Abstraction for the associated MMIO registers.
 type Registers = MMIODerefWrapper<RegisterBlock>;
 
-#[derive(PartialEq)]
-enum BlockingMode {
-    Blocking,
-    NonBlocking,
-}
+/// Time between two polls of the RX FIFO. Receiving one character takes ~87 µs at 115200 baud.
+const RX_POLL_INTERVAL: Duration = Duration::from_micros(20);
 
 struct PL011UartInner {
     registers: Registers,
     chars_written: usize,
     chars_read: usize,
+
+    /// Number of `read_char_timeout()` calls that are currently polling.
+    rx_waiters: usize,
+
+    /// Number of characters the TX side takes at once while empty. One until the FIFO is enabled.
+    tx_slots: usize,
+
+    /// `Some` if line-buffered mode is active.
+    line_buffer: Option<Vec<char>>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -232,7 +240,7 @@ struct PL011UartInner {
 
 /// Representation of the UART.
 pub struct PL011Uart {
-    inner: IRQSafeNullLock<PL011UartInner>,
+    inner: Spinlock<PL011UartInner>,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -240,6 +248,12 @@ pub struct PL011Uart {
 //--------------------------------------------------------------------------------------------------
 
 impl PL011UartInner {
+    /// Number of characters that are accumulated in line-buffered mode before a flush is forced.
+    const LINE_BUFFER_SIZE: usize = 128;
+
+    /// Depth of the TX FIFO.
+    const TX_FIFO_DEPTH: usize = 16;
+
     /// Create an instance.
     ///
     /// # Safety
@@ -250,6 +264,9 @@ impl PL011UartInner {
             registers: Registers::new(mmio_start_addr),
             chars_written: 0,
             chars_read: 0,
+            rx_waiters: 0,
+            tx_slots: 1,
+            line_buffer: None,
         }
     }
 
@@ -299,6 +316,7 @@ impl PL011UartInner {
         self.registers
             .LCR_H
             .write(LCR_H::WLEN::EightBit + LCR_H::FEN::FifosEnabled);
+        self.tx_slots = Self::TX_FIFO_DEPTH;
 
         // Set RX FIFO fill level at 1/8.
         self.registers.IFLS.write(IFLS::RXIFLSEL::OneEigth);
@@ -328,33 +346,89 @@ impl PL011UartInner {
     }
 
     /// Send a slice of characters.
+    ///
+    /// An empty TX FIFO takes a whole run of characters, so the flags are only polled once per run
+    /// instead of once per character.
     fn write_array(&mut self, a: &[char]) {
-        for c in a {
-            self.write_char(*c);
+        for run in a.chunks(self.tx_slots) {
+            // Spin until TX FIFO empty is set.
+            while !self.registers.FR.matches_all(FR::TXFE::SET) {
+                cpu::nop();
+            }
+
+            for c in run {
+                self.registers.DR.set(*c as u32);
+            }
+
+            self.chars_written += run.len();
+        }
+    }
+
+    /// Queue a character in the line buffer, or send it right away if line-buffered mode is off.
+    ///
+    /// The buffer is drained on a newline or when it is full.
+    fn write_char_buffered(&mut self, c: char) {
+        let buf = match self.line_buffer.as_mut() {
+            None => return self.write_char(c),
+            Some(buf) => buf,
+        };
+
+        buf.push(c);
+
+        if (c == '\n') || (buf.len() == Self::LINE_BUFFER_SIZE) {
+            self.flush_line_buffer();
+        }
+    }
+
+    /// Send out everything that is pending in the line buffer.
+    ///
+    /// The buffer is sent with `write_array()`, so the TX FIFO is polled once per FIFO depth of
+    /// characters.
+    fn flush_line_buffer(&mut self) {
+        if let Some(mut buf) = self.line_buffer.take() {
+            self.write_array(&buf);
+            buf.clear();
+
+            self.line_buffer = Some(buf);
+        }
+    }
+
+    /// Switch line-buffered mode on by passing a buffer, or off by passing `None`.
+    ///
+    /// Returns the buffer that is not needed anymore, if any. Switching off drains pending
+    /// characters first.
+    fn set_line_buffered(&mut self, buf: Option<Vec<char>>) -> Option<Vec<char>> {
+        match buf {
+            Some(buf) if self.line_buffer.is_none() => {
+                self.line_buffer = Some(buf);
+
+                None
+            }
+            Some(buf) => Some(buf),
+            None => {
+                self.flush_line_buffer();
+
+                self.line_buffer.take()
+            }
         }
     }
 
     /// Block execution until the last buffered character has been physically put on the TX wire.
     fn flush(&self) {
-        // Spin until the busy bit is cleared.
-        while self.registers.FR.matches_all(FR::BUSY::SET) {
+        // Spin until the TX FIFO is empty and the last character left the shift register.
+        while !self
+            .registers
+            .FR
+            .matches_all(FR::TXFE::SET + FR::BUSY::CLEAR)
+        {
             cpu::nop();
         }
     }
 
-    /// Retrieve a character.
-    fn read_char_converting(&mut self, blocking_mode: BlockingMode) -> Option<char> {
-        // If RX FIFO is empty,
+    /// Retrieve a character, if one is available.
+    fn read_char_converting(&mut self) -> Option<char> {
         if self.registers.FR.matches_all(FR::RXFE::SET) {
-            // immediately return in non-blocking mode.
-            if blocking_mode == BlockingMode::NonBlocking {
-                return None;
-            }
-
-            // Otherwise, wait until a char was received.
-            while self.registers.FR.matches_all(FR::RXFE::SET) {
-                cpu::nop();
-            }
+            return None;
         }
 
         // Read one character.
@@ -370,6 +444,31 @@ impl PL011UartInner {
 
         Some(ret)
     }
+
+    /// Register a reader that polls for characters.
+    ///
+    /// While any reader waits, the RX IRQs are masked, so that the IRQ handler does not echo, and
+    /// thereby snatch, the awaited characters.
+    fn add_rx_waiter(&mut self) {
+        if self.rx_waiters == 0 {
+            self.registers
+                .IMSC
+                .modify(IMSC::RXIM::Disabled + IMSC::RTIM::Disabled);
+        }
+
+        self.rx_waiters += 1;
+    }
+
+    /// Unregister a reader, unmasking the RX IRQs again after the last one.
+    fn remove_rx_waiter(&mut self) {
+        self.rx_waiters -= 1;
+
+        if self.rx_waiters == 0 {
+            self.registers
+                .IMSC
+                .modify(IMSC::RXIM::Enabled + IMSC::RTIM::Enabled);
+        }
+    }
 }
 
 /// Implementing `core::fmt::Write` enables usage of the `format_args!` macros, which in turn are
@@ -384,7 +483,7 @@ impl PL011UartInner {
 impl fmt::Write for PL011UartInner {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         for c in s.chars() {
-            self.write_char(c);
+            self.write_char_buffered(c);
         }
 
         Ok(())
@@ -405,7 +504,7 @@ impl PL011Uart {
     /// - The user must ensure to provide a correct MMIO start address.
     pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
         Self {
-            inner: IRQSafeNullLock::new(PL011UartInner::new(mmio_start_addr)),
+            inner: Spinlock::new(PL011UartInner::new(mmio_start_addr)),
         }
     }
 }
@@ -428,6 +527,19 @@ impl driver::interface::DeviceDriver for PL011Uart {
         Ok(())
     }
 
+    fn probe(&self) -> Result<(), &'static str> {
+        // Bits 31:9 of FR are reserved and read as zero.
+        const FR_RESERVED_MASK: u32 = !0x1FF;
+
+        self.inner.lock(|inner| {
+            if (inner.registers.FR.get() & FR_RESERVED_MASK) != 0 {
+                return Err("Reserved FR bits not reading as zero");
+            }
+
+            Ok(())
+        })
+    }
+
     fn register_and_enable_irq_handler(
         &'static self,
         irq_number: &Self::IRQNumberType,
@@ -447,11 +559,17 @@ impl console::interface::Write for PL011Uart {
     /// Passthrough of `args` to the `core::fmt::Write` implementation, but guarded by a Mutex to
     /// serialize access.
     fn write_char(&self, c: char) {
-        self.inner.lock(|inner| inner.write_char(c));
+        self.inner.lock(|inner| {
+            inner.flush_line_buffer();
+            inner.write_char(c)
+        });
     }
 
     fn write_array(&self, a: &[char]) {
-        self.inner.lock(|inner| inner.write_array(a));
+        self.inner.lock(|inner| {
+            inner.flush_line_buffer();
+            inner.write_array(a)
+        });
     }
 
     fn write_fmt(&self, args: core::fmt::Arguments) -> fmt::Result {
@@ -461,22 +579,65 @@ impl console::interface::Write for PL011Uart {
     }
 
     fn flush(&self) {
-        // Spin until TX FIFO empty is set.
-        self.inner.lock(|inner| inner.flush());
+        self.inner.lock(|inner| {
+            inner.flush_line_buffer();
+
+            // Spin until TX FIFO empty is set.
+            inner.flush()
+        });
+    }
+
+    fn set_line_buffered(&self, enable: bool) {
+        // Allocate and free outside of the lock, because the allocator may print to the console.
+        // The buffer gets its full capacity upfront, so writing never allocates.
+        let buf = enable.then(|| Vec::with_capacity(PL011UartInner::LINE_BUFFER_SIZE));
+        let unused = self.inner.lock(|inner| inner.set_line_buffered(buf));
+
+        drop(unused);
+    }
+
+    unsafe fn panic_takeover(&self, lock_timeout: Duration) {
+        self.inner.force_unlock(lock_timeout);
+
+        // Leak the buffer instead of freeing it. The panic might have happened in the allocator.
+        let unused = self.inner.lock(|inner| inner.set_line_buffered(None));
+        core::mem::forget(unused);
+    }
+
+    fn panic_flush(&self) {
+        // Only polls the flags register.
+        unsafe { self.inner.data_unlocked() }.flush()
     }
 }
 
 impl console::interface::Read for PL011Uart {
-    fn read_char(&self) -> char {
-        self.inner
-            .lock(|inner| inner.read_char_converting(BlockingMode::Blocking).unwrap())
+    fn read_char_timeout(&self, timeout: Duration) -> Option<u8> {
+        // No deadline if it is not representable, e.g. for `Duration::MAX`.
+        let deadline = time::time_manager().uptime().checked_add(timeout);
+
+        // The lock is only taken for each poll, so that waiting neither holds off IRQs on this core
+        // nor printing on the others.
+        self.inner.lock(|inner| inner.add_rx_waiter());
+        let c = loop {
+            if let Some(c) = self.inner.lock(|inner| inner.read_char_converting()) {
+                break Some(c);
+            }
+
+            match deadline {
+                Some(d) if time::time_manager().uptime() >= d => break None,
+                _ => time::time_manager().spin_for(RX_POLL_INTERVAL),
+            }
+        };
+        self.inner.lock(|inner| inner.remove_rx_waiter());
+
+        c.map(|c| c as u8)
     }
 
     fn clear_rx(&self) {
         // Read from the RX FIFO until it is indicating empty.
         while self
             .inner
-            .lock(|inner| inner.read_char_converting(BlockingMode::NonBlocking))
+            .lock(|inner| inner.read_char_converting())
             .is_some()
         {}
     }
@@ -504,8 +665,9 @@ impl exception::asynchronous::interface::IRQHandler for PL011Uart {
 
             // Check for any kind of RX interrupt.
             if pending.matches_any(MIS::RXMIS::SET + MIS::RTMIS::SET) {
-                // Echo any received characters.
-                while let Some(c) = inner.read_char_converting(BlockingMode::NonBlocking) {
+                // Echo any received characters. Pending output goes first to keep the order.
+                inner.flush_line_buffer();
+                while let Some(c) = inner.read_char_converting() {
                     inner.write_char(c)
                 }
             }
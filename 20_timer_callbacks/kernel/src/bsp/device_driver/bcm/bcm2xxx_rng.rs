@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Hardware Random Number Generator Driver.
+//!
+//! # Resources
+//!
+//! - <https://github.com/torvalds/linux/blob/master/drivers/char/hw_random/bcm2835-rng.c>
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    cpu, driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::Spinlock,
+};
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, ReadWrite},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+// RNG registers.
+//
+// The BCM2837 peripherals documentation does not cover the RNG. Descriptions are taken from the
+// Linux driver.
+register_bitfields! {
+    u32,
+
+    /// Control Register
+    RNG_CTRL [
+        /// Random bit generator enable.
+        RBGEN OFFSET(0) NUMBITS(1) [
+            Disabled = 0,
+            Enabled = 1
+        ]
+    ],
+
+    /// Status Register
+    RNG_STATUS [
+        /// Number of words available in the FIFO.
+        VAL OFFSET(24) NUMBITS(8) [],
+
+        /// Number of initial words that the HW discards after enabling.
+        WARMUP_COUNT OFFSET(0) NUMBITS(20) []
+    ],
+
+    /// Interrupt Mask Register
+    RNG_INT_MASK [
+        /// Mask the data available interrupt.
+        INT_OFF OFFSET(0) NUMBITS(1) [
+            Unmasked = 0,
+            Masked = 1
+        ]
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x00 => RNG_CTRL: ReadWrite<u32, RNG_CTRL::Register>),
+        (0x04 => RNG_STATUS: ReadWrite<u32, RNG_STATUS::Register>),
+        (0x08 => RNG_DATA: ReadOnly<u32>),
+        (0x0C => _reserved1),
+        (0x10 => RNG_INT_MASK: ReadWrite<u32, RNG_INT_MASK::Register>),
+        (0x14 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct RNGInner {
+    registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the RNG.
+pub struct RNG {
+    inner: Spinlock<RNGInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl RNGInner {
+    /// The first numbers after enabling are less random. The HW discards this many of them.
+    const WARMUP_COUNT: u32 = 0x40000;
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+        }
+    }
+
+    /// Set up the warm-up discard and enable the generator.
+    pub fn init(&mut self) {
+        // The driver polls, so the interrupt is not needed.
+        self.registers
+            .RNG_INT_MASK
+            .modify(RNG_INT_MASK::INT_OFF::Masked);
+
+        self.registers
+            .RNG_STATUS
+            .write(RNG_STATUS::WARMUP_COUNT.val(Self::WARMUP_COUNT));
+        self.registers.RNG_CTRL.write(RNG_CTRL::RBGEN::Enabled);
+    }
+
+    /// Wait for a word in the FIFO and return it.
+    pub fn next_u32(&mut self) -> u32 {
+        // The FIFO stays empty until the warm-up words have been discarded.
+        while self.registers.RNG_STATUS.read(RNG_STATUS::VAL) == 0 {
+            cpu::nop();
+        }
+
+        self.registers.RNG_DATA.get()
+    }
+
+    /// Fill `dest` with random bytes.
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl RNG {
+    pub const COMPATIBLE: &'static str = "BCM RNG";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: Spinlock::new(RNGInner::new(mmio_start_addr)),
+        }
+    }
+
+    /// Return a random word.
+    ///
+    /// Blocks until the HW has one available. Right after init, this includes the warm-up.
+    pub fn next_u32(&self) -> u32 {
+        self.inner.lock(|inner| inner.next_u32())
+    }
+
+    /// Fill `dest` with random bytes.
+    pub fn fill_bytes(&self, dest: &mut [u8]) {
+        self.inner.lock(|inner| inner.fill_bytes(dest))
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for RNG {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.init());
+
+        Ok(())
+    }
+
+    fn probe(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            if !inner
+                .registers
+                .RNG_CTRL
+                .matches_all(RNG_CTRL::RBGEN::Enabled)
+            {
+                return Err("Generator enable bit not reading back as set");
+            }
+
+            Ok(())
+        })
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::{addr_of_mut, read_volatile, write_volatile};
+    use test_macros::kernel_test;
+
+    const CTRL_INDEX: usize = 0x00 / 4;
+    const STATUS_INDEX: usize = 0x04 / 4;
+    const DATA_INDEX: usize = 0x08 / 4;
+    const INT_MASK_INDEX: usize = 0x10 / 4;
+
+    /// Plain memory standing in for the MMIO registers.
+    static mut MOCK_REGISTERS: [u32; 0x14 / 4] = [0; 0x14 / 4];
+
+    /// Init must program the warm-up and enable the generator, and reads must come from the FIFO.
+    #[kernel_test]
+    fn rng_init_and_read() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = RNGInner::new(Address::new(mock as usize));
+
+            inner.init();
+            assert_eq!(read_volatile(mock.add(CTRL_INDEX)), 1);
+            assert_eq!(
+                read_volatile(mock.add(STATUS_INDEX)),
+                RNGInner::WARMUP_COUNT
+            );
+            assert_eq!(read_volatile(mock.add(INT_MASK_INDEX)), 1);
+
+            // Pretend that the HW has words available.
+            write_volatile(mock.add(STATUS_INDEX), 1 << 24);
+            write_volatile(mock.add(DATA_INDEX), 0x0403_0201);
+            assert_eq!(inner.next_u32(), 0x0403_0201);
+
+            // A short last chunk must only take the bytes it needs.
+            let mut buf = [0_u8; 6];
+            inner.fill_bytes(&mut buf);
+            assert_eq!(buf, [1, 2, 3, 4, 1, 2]);
+        }
+    }
+}
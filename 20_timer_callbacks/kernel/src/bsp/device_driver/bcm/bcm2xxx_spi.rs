@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! SPI0 Master Driver.
+//!
+//! The SPI0 signals are on GPIO 7-11 in alternate function 0, which must be selected separately,
+//! e.g. with `GPIO::map_spi0()`.
+//!
+//! # Resources
+//!
+//! - <https://github.com/raspberrypi/documentation/files/1888662/BCM2837-ARM-Peripherals.-.Revised.-.V2-1.pdf>
+//! - <https://datasheets.raspberrypi.org/bcm2711/bcm2711-peripherals.pdf>
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    cpu, driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::Spinlock,
+};
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::ReadWrite,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+// SPI registers.
+//
+// Descriptions taken from
+// - https://github.com/raspberrypi/documentation/files/1888662/BCM2837-ARM-Peripherals.-.Revised.-.V2-1.pdf
+register_bitfields! {
+    u32,
+
+    /// Master Control and Status
+    CS [
+        /// TX FIFO can accept data.
+        TXD OFFSET(18) NUMBITS(1) [],
+
+        /// RX FIFO contains data.
+        RXD OFFSET(17) NUMBITS(1) [],
+
+        /// Transfer is complete.
+        DONE OFFSET(16) NUMBITS(1) [],
+
+        /// Transfer Active.
+        TA OFFSET(7) NUMBITS(1) [],
+
+        /// Clear FIFO. Reads as zero.
+        CLEAR OFFSET(4) NUMBITS(2) [
+            None = 0b00,
+            Tx = 0b01,
+            Rx = 0b10,
+            Both = 0b11
+        ],
+
+        /// Clock Polarity.
+        CPOL OFFSET(3) NUMBITS(1) [],
+
+        /// Clock Phase.
+        CPHA OFFSET(2) NUMBITS(1) [],
+
+        /// Chip Select.
+        CHIP_SELECT OFFSET(0) NUMBITS(2) [
+            CS0 = 0b00,
+            CS1 = 0b01,
+            CS2 = 0b10
+        ]
+    ],
+
+    /// Master Clock Divider
+    CLK [
+        /// SCLK = core clock / CDIV. Zero means 65536. Odd values are rounded down by the HW.
+        CDIV OFFSET(0) NUMBITS(16) []
+    ],
+
+    /// Master Data Length
+    DLEN [
+        /// Number of bytes to transfer. Only used in DMA mode.
+        LEN OFFSET(0) NUMBITS(16) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x00 => CS: ReadWrite<u32, CS::Register>),
+        (0x04 => FIFO: ReadWrite<u32>),
+        (0x08 => CLK: ReadWrite<u32, CLK::Register>),
+        (0x0C => DLEN: ReadWrite<u32, DLEN::Register>),
+        (0x10 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct SPIInner {
+    registers: Registers,
+    core_clock_hz: u32,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the SPI0 master.
+pub struct SPI {
+    inner: Spinlock<SPIInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Compute the smallest clock divider that does not exceed `sclk_hz`.
+///
+/// The HW rounds odd dividers down, so the result is rounded up to the next even value.
+const fn clock_divider(core_clock_hz: u32, sclk_hz: u32) -> u16 {
+    let div = (core_clock_hz + sclk_hz - 1) / sclk_hz;
+    let div = (div + 1) & !1;
+
+    if div > u16::MAX as u32 {
+        // Zero means 65536, the slowest possible clock.
+        0
+    } else {
+        div as u16
+    }
+}
+
+impl SPIInner {
+    const DEFAULT_SCLK_HZ: u32 = 1_000_000;
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>, core_clock_hz: u32) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            core_clock_hz,
+        }
+    }
+
+    /// Select mode 0 on CS0, empty the FIFOs and set the default clock.
+    pub fn init(&mut self) {
+        self.registers
+            .CS
+            .write(CS::CHIP_SELECT::CS0 + CS::CLEAR::Both + CS::TA::CLEAR);
+        self.registers.DLEN.set(0);
+        self.set_clock_divider(clock_divider(self.core_clock_hz, Self::DEFAULT_SCLK_HZ));
+    }
+
+    /// Set the divider of the core clock.
+    pub fn set_clock_divider(&mut self, div: u16) {
+        self.registers.CLK.write(CLK::CDIV.val(div as u32));
+    }
+
+    /// Send `tx` and receive into `rx` at the same time.
+    pub fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> Result<(), &'static str> {
+        if tx.len() != rx.len() {
+            return Err("TX and RX buffers differ in length");
+        }
+
+        // Empty the FIFOs and assert CS. Status bits are read-only, so modifying is fine.
+        self.registers.CS.modify(CS::CLEAR::Both + CS::TA::SET);
+
+        // Keep the TX FIFO filled, but drain the RX FIFO in between. The HW stops clocking when
+        // the RX FIFO is full.
+        let (mut tx_pos, mut rx_pos) = (0, 0);
+        while rx_pos < rx.len() {
+            if (tx_pos < tx.len()) && self.registers.CS.is_set(CS::TXD) {
+                self.registers.FIFO.set(tx[tx_pos] as u32);
+                tx_pos += 1;
+            }
+
+            if self.registers.CS.is_set(CS::RXD) {
+                rx[rx_pos] = self.registers.FIFO.get() as u8;
+                rx_pos += 1;
+            }
+        }
+
+        while !self.registers.CS.is_set(CS::DONE) {
+            cpu::nop();
+        }
+
+        // Deassert CS.
+        self.registers.CS.modify(CS::TA::CLEAR);
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl SPI {
+    pub const COMPATIBLE: &'static str = "BCM SPI0";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    /// - `core_clock_hz` must be the clock the SPI block runs on.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>, core_clock_hz: u32) -> Self {
+        Self {
+            inner: Spinlock::new(SPIInner::new(mmio_start_addr, core_clock_hz)),
+        }
+    }
+
+    /// Set the divider of the core clock, which yields the SCLK frequency.
+    ///
+    /// Zero means 65536. The HW rounds odd values down.
+    pub fn set_clock_divider(&self, div: u16) {
+        self.inner.lock(|inner| inner.set_clock_divider(div))
+    }
+
+    /// Do a full-duplex transfer on CS0.
+    ///
+    /// CS is asserted for the whole transfer. `tx` and `rx` must have the same length. Blocks until
+    /// all bytes have been clocked through.
+    pub fn transfer(&self, tx: &[u8], rx: &mut [u8]) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.transfer(tx, rx))
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for SPI {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    unsafe fn init(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.init());
+
+        Ok(())
+    }
+
+    fn probe(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| {
+            // With no transfer active, the TX FIFO must have space.
+            if !inner.registers.CS.is_set(CS::TXD) {
+                return Err("TX FIFO not reporting space while idle");
+            }
+
+            Ok(())
+        })
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::{addr_of_mut, read_volatile, write_volatile};
+    use test_macros::kernel_test;
+
+    const CS_INDEX: usize = 0x00 / 4;
+    const CLK_INDEX: usize = 0x08 / 4;
+
+    /// Plain memory standing in for the MMIO registers.
+    ///
+    /// A written FIFO word reads back unchanged, like with MOSI wired to MISO.
+    static mut MOCK_REGISTERS: [u32; 0x10 / 4] = [0; 0x10 / 4];
+
+    /// The default clock must be 1 MHz on both core clocks, and never faster than requested.
+    #[kernel_test]
+    fn clock_divider_is_derived_from_core_clock() {
+        assert_eq!(clock_divider(250_000_000, 1_000_000), 250);
+        assert_eq!(clock_divider(500_000_000, 1_000_000), 500);
+        assert_eq!(clock_divider(250_000_000, 3_000_000), 84);
+        assert_eq!(clock_divider(500_000_000, 1_000), 0);
+    }
+
+    /// A loopback transfer must receive what was sent and leave CS deasserted.
+    #[kernel_test]
+    fn spi_loopback_transfer() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = SPIInner::new(Address::new(mock as usize), 250_000_000);
+
+            inner.set_clock_divider(64);
+            assert_eq!(read_volatile(mock.add(CLK_INDEX)), 64);
+
+            // Pretend that the HW always has FIFO space, received data and is done.
+            write_volatile(mock.add(CS_INDEX), (1 << 18) | (1 << 17) | (1 << 16));
+
+            let tx = [0xde, 0xad, 0xbe, 0xef, 0x42];
+            let mut rx = [0_u8; 5];
+            inner.transfer(&tx, &mut rx).unwrap();
+            assert_eq!(rx, tx);
+            assert_eq!(read_volatile(mock.add(CS_INDEX)) & (1 << 7), 0);
+
+            let mut short = [0_u8; 2];
+            assert!(inner.transfer(&tx, &mut short).is_err());
+        }
+    }
+}
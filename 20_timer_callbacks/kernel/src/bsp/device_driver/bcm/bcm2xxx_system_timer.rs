@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! System Timer Driver.
+//!
+//! A free-running 64 bit counter that ticks at 1 MHz, plus four 32 bit compare registers that raise
+//! an IRQ when the lower counter word matches them.
+//!
+//! # Resources
+//!
+//! - <https://github.com/raspberrypi/documentation/files/1888662/BCM2837-ARM-Peripherals.-.Revised.-.V2-1.pdf>
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    driver,
+    exception::{self, asynchronous::IRQNumber},
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::{InitStateLock, Spinlock},
+};
+use core::sync::atomic::{AtomicBool, Ordering};
+use tock_registers::{
+    interfaces::{Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::{ReadOnly, ReadWrite},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+// System Timer registers.
+//
+// Descriptions taken from "BCM2837 ARM Peripherals", chapter 12.
+register_bitfields! {
+    u32,
+
+    /// Control/Status Register
+    CS [
+        /// Compare channel 3 matched. Write 1 to clear.
+        M3 OFFSET(3) NUMBITS(1) [],
+
+        /// Compare channel 2 matched. Write 1 to clear.
+        M2 OFFSET(2) NUMBITS(1) [],
+
+        /// Compare channel 1 matched. Write 1 to clear.
+        M1 OFFSET(1) NUMBITS(1) [],
+
+        /// Compare channel 0 matched. Write 1 to clear.
+        M0 OFFSET(0) NUMBITS(1) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x00 => CS: ReadWrite<u32, CS::Register>),
+        (0x04 => CLO: ReadOnly<u32>),
+        (0x08 => CHI: ReadOnly<u32>),
+        (0x0C => C: [ReadWrite<u32>; 4]),
+        (0x1C => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct SystemTimerInner {
+    registers: Registers,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the System Timer.
+pub struct SystemTimer {
+    inner: Spinlock<SystemTimerInner>,
+    irq_number: InitStateLock<Option<IRQNumber>>,
+    irq_enabled: AtomicBool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl SystemTimerInner {
+    /// Compare channels 0 and 2 are used by the VideoCore firmware.
+    const FREE_CHANNELS: [usize; 2] = [1, 3];
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+        }
+    }
+
+    /// Read the 64 bit counter.
+    ///
+    /// The two halves can't be read atomically, so the upper one is read again to detect a carry in
+    /// between.
+    pub fn now_micros(&self) -> u64 {
+        let mut hi = self.registers.CHI.get();
+
+        loop {
+            let lo = self.registers.CLO.get();
+            let hi_again = self.registers.CHI.get();
+
+            if hi == hi_again {
+                return ((hi as u64) << 32) | lo as u64;
+            }
+
+            hi = hi_again;
+        }
+    }
+
+    /// Arm compare `channel` for when the lower counter word reaches `target`.
+    pub fn set_compare(&mut self, channel: usize, target: u32) -> Result<(), &'static str> {
+        if !Self::FREE_CHANNELS.contains(&channel) {
+            return Err("Compare channel is reserved by the VideoCore");
+        }
+
+        // Clear a stale match first, so it can't be mistaken for the new one.
+        self.registers.CS.set(1 << channel);
+        self.registers.C[channel].set(target);
+
+        Ok(())
+    }
+
+    /// Return whether compare `channel` matched and was not acknowledged yet.
+    pub fn has_matched(&self, channel: usize) -> bool {
+        self.registers.CS.get() & (1 << channel) != 0
+    }
+
+    /// Acknowledge all pending matches of the free channels.
+    pub fn clear_matches(&mut self) {
+        let mask = Self::FREE_CHANNELS
+            .iter()
+            .fold(0, |mask, channel| mask | (1 << channel));
+
+        self.registers.CS.set(self.registers.CS.get() & mask);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl SystemTimer {
+    pub const COMPATIBLE: &'static str = "BCM System Timer";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: Spinlock::new(SystemTimerInner::new(mmio_start_addr)),
+            irq_number: InitStateLock::new(None),
+            irq_enabled: AtomicBool::new(false),
+        }
+    }
+
+    /// The current counter value, which is the time since power-on in microseconds.
+    pub fn now_micros(&self) -> u64 {
+        self.inner.lock(|inner| inner.now_micros())
+    }
+
+    /// Arm compare `channel` to match when the lower 32 bits of the counter reach `target`.
+    ///
+    /// Only channels 1 and 3 are usable. The BSP routes the IRQ of channel 1 to this driver, which
+    /// acknowledges the match. A match on channel 3 must be polled with `has_matched()`.
+    ///
+    /// The IRQ is enabled in the interrupt controller with the first call.
+    pub fn set_compare(&self, channel: usize, target: u32) -> Result<(), &'static str> {
+        self.inner
+            .lock(|inner| inner.set_compare(channel, target))?;
+
+        if let Some(irq_number) = self.irq_number.read(|irq_number| *irq_number) {
+            if !self.irq_enabled.swap(true, Ordering::Relaxed) {
+                exception::asynchronous::irq_manager().enable(&irq_number);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return whether compare `channel` matched and was not acknowledged yet.
+    pub fn has_matched(&self, channel: usize) -> bool {
+        self.inner.lock(|inner| inner.has_matched(channel))
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::{Mutex, ReadWriteEx};
+
+impl driver::interface::DeviceDriver for SystemTimer {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+
+    fn register_and_enable_irq_handler(
+        &'static self,
+        irq_number: &Self::IRQNumberType,
+    ) -> Result<(), &'static str> {
+        use exception::asynchronous::{irq_manager, IRQHandlerDescriptor};
+
+        let descriptor = IRQHandlerDescriptor::new(*irq_number, Self::COMPATIBLE, self);
+
+        irq_manager().register_handler(descriptor)?;
+
+        // Enabling is deferred to the first `set_compare()`, so that an unused timer can't fire.
+        self.irq_number.write(|irq| *irq = Some(*irq_number));
+
+        Ok(())
+    }
+}
+
+impl exception::asynchronous::interface::IRQHandler for SystemTimer {
+    fn handle(&self) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.clear_matches());
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::{addr_of_mut, read_volatile, write_volatile};
+    use test_macros::kernel_test;
+
+    const CS_INDEX: usize = 0x00 / 4;
+    const CLO_INDEX: usize = 0x04 / 4;
+    const CHI_INDEX: usize = 0x08 / 4;
+    const C1_INDEX: usize = 0x10 / 4;
+
+    /// Plain memory standing in for the MMIO registers.
+    static mut MOCK_REGISTERS: [u32; 0x1C / 4] = [0; 0x1C / 4];
+
+    /// The counter halves must be combined, and only the free compare channels be usable.
+    #[kernel_test]
+    fn system_timer_counter_and_compare() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = SystemTimerInner::new(Address::new(mock as usize));
+
+            write_volatile(mock.add(CLO_INDEX), 0x8765_4321);
+            write_volatile(mock.add(CHI_INDEX), 0x1);
+            assert_eq!(inner.now_micros(), 0x1_8765_4321);
+
+            assert!(inner.set_compare(0, 1000).is_err());
+            assert!(inner.set_compare(2, 1000).is_err());
+
+            assert!(inner.set_compare(1, 1000).is_ok());
+            assert_eq!(read_volatile(mock.add(C1_INDEX)), 1000);
+
+            // Pretend that channels 1 and 2 matched.
+            write_volatile(mock.add(CS_INDEX), 0b0110);
+            assert!(inner.has_matched(1));
+
+            // Only the bit of channel 1 must be written. On real HW, this leaves the VideoCore's
+            // match on channel 2 pending.
+            inner.clear_matches();
+            assert_eq!(read_volatile(mock.add(CS_INDEX)), 0b0010);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Watchdog Driver.
+//!
+//! The watchdog is part of the power management block. Once started, it counts down and resets the
+//! SoC when it reaches zero, unless it is fed in time.
+//!
+//! The counter ticks at 65536 Hz, so the resolution is 1/65536 s (about 15.26 µs). It is 20 bits
+//! wide, which makes the longest timeout 0xF_FFFF ticks, or just below 16 s.
+//!
+//! # Resources
+//!
+//! - <https://github.com/torvalds/linux/blob/master/drivers/watchdog/bcm2835_wdt.c>
+
+use crate::{
+    bsp::device_driver::common::MMIODerefWrapper,
+    cpu, driver,
+    exception::asynchronous::IRQNumber,
+    memory::{Address, Virtual},
+    synchronization,
+    synchronization::Spinlock,
+};
+use core::time::Duration;
+use tock_registers::{
+    interfaces::{ReadWriteable, Readable, Writeable},
+    register_bitfields, register_structs,
+    registers::ReadWrite,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+// Power management registers.
+//
+// The BCM2837 peripherals documentation does not cover the power management block. Descriptions
+// are taken from the Linux driver.
+register_bitfields! {
+    u32,
+
+    /// Reset Control Register
+    PM_RSTC [
+        /// Must be written with every access, else the write is ignored.
+        PASSWD OFFSET(24) NUMBITS(8) [
+            Value = 0x5A
+        ],
+
+        /// What to do when the watchdog expires.
+        WRCFG OFFSET(4) NUMBITS(2) [
+            Clear = 0b00,
+            FullReset = 0b10
+        ]
+    ],
+
+    /// Watchdog Register
+    PM_WDOG [
+        /// Must be written with every access, else the write is ignored.
+        PASSWD OFFSET(24) NUMBITS(8) [
+            Value = 0x5A
+        ],
+
+        /// Remaining ticks until the watchdog expires.
+        TIME OFFSET(0) NUMBITS(20) []
+    ]
+}
+
+register_structs! {
+    #[allow(non_snake_case)]
+    RegisterBlock {
+        (0x00 => _reserved1),
+        (0x1C => PM_RSTC: ReadWrite<u32, PM_RSTC::Register>),
+        (0x20 => _reserved2),
+        (0x24 => PM_WDOG: ReadWrite<u32, PM_WDOG::Register>),
+        (0x28 => @END),
+    }
+}
+
+/// Abstraction for the associated MMIO registers.
+type Registers = MMIODerefWrapper<RegisterBlock>;
+
+struct WatchdogInner {
+    registers: Registers,
+
+    /// The ticks programmed by the last `start()`. `None` while the watchdog is stopped.
+    timeout_ticks: Option<u32>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Representation of the Watchdog.
+pub struct Watchdog {
+    inner: Spinlock<WatchdogInner>,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl WatchdogInner {
+    /// The frequency of the watchdog counter.
+    const TICKS_PER_SECOND: u128 = 65536;
+
+    /// The largest value the counter can hold.
+    const MAX_TICKS: u32 = 0xF_FFFF;
+
+    /// The PM_RSTC value that disarms the watchdog.
+    ///
+    /// Taken from the Linux driver (`PM_PASSWORD | PM_RSTC_RESET`). Bits 1 and 8 are undocumented,
+    /// so they are set like there instead of being cleared.
+    const RSTC_STOP: u32 = 0x5A00_0102;
+
+    /// The delay used by `reboot_now()`. Short, but long enough for the register writes to land.
+    const REBOOT_TICKS: u32 = 10;
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            registers: Registers::new(mmio_start_addr),
+            timeout_ticks: None,
+        }
+    }
+
+    /// Convert a timeout to counter ticks, rounding down.
+    fn duration_to_ticks(timeout: Duration) -> Result<u32, &'static str> {
+        let ticks = timeout.as_nanos() * Self::TICKS_PER_SECOND / 1_000_000_000;
+
+        if ticks == 0 {
+            return Err("Timeout is shorter than one watchdog tick");
+        }
+
+        if ticks > Self::MAX_TICKS as u128 {
+            return Err("Timeout exceeds the maximum watchdog timeout");
+        }
+
+        Ok(ticks as u32)
+    }
+
+    /// Load the counter and arm a full reset on expiry.
+    fn arm(&mut self, ticks: u32) {
+        self.registers
+            .PM_WDOG
+            .write(PM_WDOG::PASSWD::Value + PM_WDOG::TIME.val(ticks));
+
+        // Keep the other configuration bits, but the password must replace whatever was read.
+        let rstc = self.registers.PM_RSTC.extract();
+        self.registers
+            .PM_RSTC
+            .modify_no_read(rstc, PM_RSTC::PASSWD::Value + PM_RSTC::WRCFG::FullReset);
+    }
+
+    pub fn start(&mut self, timeout: Duration) -> Result<(), &'static str> {
+        let ticks = Self::duration_to_ticks(timeout)?;
+
+        self.arm(ticks);
+        self.timeout_ticks = Some(ticks);
+
+        Ok(())
+    }
+
+    pub fn feed(&mut self) {
+        // Without a preceding start, there is no timeout to reload.
+        if let Some(ticks) = self.timeout_ticks {
+            self.arm(ticks);
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.registers.PM_RSTC.set(Self::RSTC_STOP);
+        self.timeout_ticks = None;
+    }
+
+    pub fn reboot_now(&mut self) {
+        self.arm(Self::REBOOT_TICKS);
+        self.timeout_ticks = None;
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl Watchdog {
+    pub const COMPATIBLE: &'static str = "BCM Watchdog";
+
+    /// Create an instance.
+    ///
+    /// # Safety
+    ///
+    /// - The user must ensure to provide a correct MMIO start address.
+    pub const unsafe fn new(mmio_start_addr: Address<Virtual>) -> Self {
+        Self {
+            inner: Spinlock::new(WatchdogInner::new(mmio_start_addr)),
+        }
+    }
+
+    /// Start the watchdog, or restart it with a new timeout.
+    ///
+    /// The timeout is rounded down to whole ticks. It must be at least one tick and at most
+    /// 0xF_FFFF ticks (just below 16 s).
+    pub fn start(&self, timeout: Duration) -> Result<(), &'static str> {
+        self.inner.lock(|inner| inner.start(timeout))
+    }
+
+    /// Reload the counter with the timeout of the last `start()`.
+    ///
+    /// Does nothing if the watchdog was not started.
+    pub fn feed(&self) {
+        self.inner.lock(|inner| inner.feed())
+    }
+
+    /// Stop the watchdog.
+    pub fn stop(&self) {
+        self.inner.lock(|inner| inner.stop())
+    }
+
+    /// Reset the SoC.
+    pub fn reboot_now(&self) -> ! {
+        self.inner.lock(|inner| inner.reboot_now());
+
+        cpu::wait_forever()
+    }
+}
+
+//------------------------------------------------------------------------------
+// OS Interface Code
+//------------------------------------------------------------------------------
+use synchronization::interface::Mutex;
+
+impl driver::interface::DeviceDriver for Watchdog {
+    type IRQNumberType = IRQNumber;
+
+    fn compatible(&self) -> &'static str {
+        Self::COMPATIBLE
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr::{addr_of_mut, read_volatile, write_volatile};
+    use test_macros::kernel_test;
+
+    const RSTC_INDEX: usize = 0x1C / 4;
+    const WDOG_INDEX: usize = 0x24 / 4;
+
+    /// Plain memory standing in for the MMIO registers.
+    static mut MOCK_REGISTERS: [u32; 0x28 / 4] = [0; 0x28 / 4];
+
+    /// Timeouts must be converted to ticks and checked against the counter width.
+    #[kernel_test]
+    fn watchdog_timeout_conversion() {
+        assert_eq!(
+            WatchdogInner::duration_to_ticks(Duration::from_secs(1)),
+            Ok(65536)
+        );
+        assert_eq!(
+            WatchdogInner::duration_to_ticks(Duration::from_millis(500)),
+            Ok(32768)
+        );
+        assert!(WatchdogInner::duration_to_ticks(Duration::from_micros(10)).is_err());
+        assert!(WatchdogInner::duration_to_ticks(Duration::from_secs(16)).is_err());
+    }
+
+    /// Feeding must not touch the HW before a start, and all writes must carry the password.
+    #[kernel_test]
+    fn watchdog_feed_and_start() {
+        unsafe {
+            let mock = addr_of_mut!(MOCK_REGISTERS) as *mut u32;
+            let mut inner = WatchdogInner::new(Address::new(mock as usize));
+
+            inner.feed();
+            assert_eq!(read_volatile(mock.add(WDOG_INDEX)), 0);
+            assert_eq!(read_volatile(mock.add(RSTC_INDEX)), 0);
+
+            // Bits outside of WRCFG must survive.
+            write_volatile(mock.add(RSTC_INDEX), 0x0000_0102);
+            assert!(inner.start(Duration::from_secs(1)).is_ok());
+            assert_eq!(read_volatile(mock.add(WDOG_INDEX)), 0x5A01_0000);
+            assert_eq!(read_volatile(mock.add(RSTC_INDEX)), 0x5A00_0122);
+
+            // Pretend the counter ran down a bit.
+            write_volatile(mock.add(WDOG_INDEX), 0x5A00_1234);
+            inner.feed();
+            assert_eq!(read_volatile(mock.add(WDOG_INDEX)), 0x5A01_0000);
+
+            inner.stop();
+            assert_eq!(read_volatile(mock.add(RSTC_INDEX)), 0x5A00_0102);
+            write_volatile(mock.add(WDOG_INDEX), 0);
+            inner.feed();
+            assert_eq!(read_volatile(mock.add(WDOG_INDEX)), 0);
+        }
+    }
+}
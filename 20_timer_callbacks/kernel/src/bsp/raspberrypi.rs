@@ -7,7 +7,9 @@
 pub mod cpu;
 pub mod driver;
 pub mod exception;
+pub mod led;
 pub mod memory;
+pub mod time;
 
 //--------------------------------------------------------------------------------------------------
 // Public Code
@@ -4,11 +4,47 @@
 
 //! BSP Processor code.
 
+use super::memory::mmu::virt_boot_core_stack_region;
+use crate::memory::{Address, Virtual};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Physical addresses of the spin table entries, indexed by core id.
+///
+/// The firmware's ARM stub parks the secondary cores in a loop that waits for an event, then jumps
+/// to the address found in the core's entry if it is not zero. This is the same on the RPi3 and the
+/// RPi4. PSCI is only available if the stub is replaced by Trusted Firmware.
+const SPIN_TABLE: [usize; NUM_CORES] = [0xD8, 0xE0, 0xE8, 0xF0];
+
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
 
+/// The number of processor cores.
+pub const NUM_CORES: usize = 4;
+
 /// Used by `arch` code to find the early boot core.
 #[no_mangle]
 #[link_section = ".text._start_arguments"]
 pub static BOOT_CORE_ID: u64 = 0;
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the virtual address of the spin table entry that releases `core`.
+///
+/// Returns `None` for the boot core and for cores that do not exist.
+pub fn secondary_core_release_addr(core: usize) -> Option<Address<Virtual>> {
+    if core as u64 == BOOT_CORE_ID {
+        return None;
+    }
+
+    // The spin table lives in the first page of DRAM, which is mapped as the bottom of the boot
+    // core's stack.
+    SPIN_TABLE
+        .get(core)
+        .map(|offset| virt_boot_core_stack_region().start_addr() + *offset)
+}
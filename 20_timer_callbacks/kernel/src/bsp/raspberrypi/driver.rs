@@ -17,13 +17,47 @@ use core::{
     sync::atomic::{AtomicBool, Ordering},
 };
 
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The VPU core clock, which the mini UART baud rate and the SPI clock are derived from.
+#[cfg(all(
+    feature = "bsp_rpi3",
+    any(feature = "console_mini_uart", feature = "spi")
+))]
+const CORE_CLOCK_HZ: u32 = 250_000_000;
+
+/// The VPU core clock, which the mini UART baud rate and the SPI clock are derived from.
+#[cfg(all(
+    feature = "bsp_rpi4",
+    any(feature = "console_mini_uart", feature = "spi")
+))]
+const CORE_CLOCK_HZ: u32 = 500_000_000;
+
 //--------------------------------------------------------------------------------------------------
 // Global instances
 //--------------------------------------------------------------------------------------------------
 
 static mut PL011_UART: MaybeUninit<device_driver::PL011Uart> = MaybeUninit::uninit();
+#[cfg(feature = "console_mini_uart")]
+static mut MINI_UART: MaybeUninit<device_driver::MiniUart> = MaybeUninit::uninit();
 static mut GPIO: MaybeUninit<device_driver::GPIO> = MaybeUninit::uninit();
 
+#[cfg(feature = "spi")]
+static mut SPI: MaybeUninit<device_driver::SPI> = MaybeUninit::uninit();
+static mut MAILBOX: MaybeUninit<device_driver::Mailbox> = MaybeUninit::uninit();
+#[cfg(feature = "framebuffer")]
+static mut FRAMEBUFFER: MaybeUninit<device_driver::Framebuffer> = MaybeUninit::uninit();
+static mut SYSTEM_TIMER: MaybeUninit<device_driver::SystemTimer> = MaybeUninit::uninit();
+static mut WATCHDOG: MaybeUninit<device_driver::Watchdog> = MaybeUninit::uninit();
+
+/// Set once `WATCHDOG` is written, so that the panic path can tell whether it may be used.
+static WATCHDOG_READY: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "bsp_rpi3")]
+static mut RNG: MaybeUninit<device_driver::RNG> = MaybeUninit::uninit();
+
 #[cfg(feature = "bsp_rpi3")]
 static mut INTERRUPT_CONTROLLER: MaybeUninit<device_driver::InterruptController> =
     MaybeUninit::uninit();
@@ -48,11 +82,32 @@ unsafe fn instantiate_uart() -> Result<(), &'static str> {
 
 /// This must be called only after successful init of the UART driver.
 unsafe fn post_init_uart() -> Result<(), &'static str> {
+    #[cfg(not(feature = "console_mini_uart"))]
     console::register_console(PL011_UART.assume_init_ref());
 
     Ok(())
 }
 
+/// This must be called only after successful init of the memory subsystem.
+#[cfg(feature = "console_mini_uart")]
+unsafe fn instantiate_mini_uart() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::AUX_START, mmio::AUX_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::MiniUart::COMPATIBLE, &mmio_descriptor)?;
+
+    MINI_UART.write(device_driver::MiniUart::new(virt_addr, CORE_CLOCK_HZ));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the mini UART driver.
+#[cfg(feature = "console_mini_uart")]
+unsafe fn post_init_mini_uart() -> Result<(), &'static str> {
+    console::register_console(MINI_UART.assume_init_ref());
+
+    Ok(())
+}
+
 /// This must be called only after successful init of the memory subsystem.
 unsafe fn instantiate_gpio() -> Result<(), &'static str> {
     let mmio_descriptor = MMIODescriptor::new(mmio::GPIO_START, mmio::GPIO_SIZE);
@@ -66,7 +121,84 @@ unsafe fn instantiate_gpio() -> Result<(), &'static str> {
 
 /// This must be called only after successful init of the GPIO driver.
 unsafe fn post_init_gpio() -> Result<(), &'static str> {
+    #[cfg(not(feature = "console_mini_uart"))]
     GPIO.assume_init_ref().map_pl011_uart();
+
+    #[cfg(feature = "console_mini_uart")]
+    GPIO.assume_init_ref().map_mini_uart();
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+#[cfg(feature = "spi")]
+unsafe fn instantiate_spi() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::SPI0_START, mmio::SPI0_SIZE);
+    let virt_addr = memory::mmu::kernel_map_mmio(device_driver::SPI::COMPATIBLE, &mmio_descriptor)?;
+
+    SPI.write(device_driver::SPI::new(virt_addr, CORE_CLOCK_HZ));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the GPIO and SPI drivers.
+#[cfg(feature = "spi")]
+unsafe fn post_init_spi() -> Result<(), &'static str> {
+    GPIO.assume_init_ref().map_spi0();
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_mailbox() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::MAILBOX_START, mmio::MAILBOX_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Mailbox::COMPATIBLE, &mmio_descriptor)?;
+
+    MAILBOX.write(device_driver::Mailbox::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful instantiation of the mailbox driver.
+#[cfg(feature = "framebuffer")]
+unsafe fn instantiate_framebuffer() -> Result<(), &'static str> {
+    FRAMEBUFFER.write(device_driver::Framebuffer::new(MAILBOX.assume_init_ref()));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_system_timer() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::SYSTEM_TIMER_START, mmio::SYSTEM_TIMER_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::SystemTimer::COMPATIBLE, &mmio_descriptor)?;
+
+    SYSTEM_TIMER.write(device_driver::SystemTimer::new(virt_addr));
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+unsafe fn instantiate_watchdog() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::PM_START, mmio::PM_SIZE);
+    let virt_addr =
+        memory::mmu::kernel_map_mmio(device_driver::Watchdog::COMPATIBLE, &mmio_descriptor)?;
+
+    WATCHDOG.write(device_driver::Watchdog::new(virt_addr));
+    WATCHDOG_READY.store(true, Ordering::Release);
+
+    Ok(())
+}
+
+/// This must be called only after successful init of the memory subsystem.
+#[cfg(feature = "bsp_rpi3")]
+unsafe fn instantiate_rng() -> Result<(), &'static str> {
+    let mmio_descriptor = MMIODescriptor::new(mmio::RNG_START, mmio::RNG_SIZE);
+    let virt_addr = memory::mmu::kernel_map_mmio(device_driver::RNG::COMPATIBLE, &mmio_descriptor)?;
+
+    RNG.write(device_driver::RNG::new(virt_addr));
+
     Ok(())
 }
 
@@ -129,6 +261,21 @@ unsafe fn driver_uart() -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+#[cfg(feature = "console_mini_uart")]
+unsafe fn driver_mini_uart() -> Result<(), &'static str> {
+    instantiate_mini_uart()?;
+
+    let mini_uart_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        MINI_UART.assume_init_ref(),
+        Some(post_init_mini_uart),
+        None,
+    );
+    generic_driver::driver_manager().register_driver(mini_uart_descriptor);
+
+    Ok(())
+}
+
 /// Function needs to ensure that driver registration happens only after correct instantiation.
 unsafe fn driver_gpio() -> Result<(), &'static str> {
     instantiate_gpio()?;
@@ -143,6 +290,81 @@ unsafe fn driver_gpio() -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+#[cfg(feature = "spi")]
+unsafe fn driver_spi() -> Result<(), &'static str> {
+    instantiate_spi()?;
+
+    let spi_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        SPI.assume_init_ref(),
+        Some(post_init_spi),
+        None,
+    );
+    generic_driver::driver_manager().register_driver(spi_descriptor);
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_mailbox() -> Result<(), &'static str> {
+    instantiate_mailbox()?;
+
+    let mailbox_descriptor =
+        generic_driver::DeviceDriverDescriptor::new(MAILBOX.assume_init_ref(), None, None);
+    generic_driver::driver_manager().register_driver(mailbox_descriptor);
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+#[cfg(feature = "framebuffer")]
+unsafe fn driver_framebuffer() -> Result<(), &'static str> {
+    instantiate_framebuffer()?;
+
+    let framebuffer_descriptor =
+        generic_driver::DeviceDriverDescriptor::new(FRAMEBUFFER.assume_init_ref(), None, None);
+    generic_driver::driver_manager().register_driver(framebuffer_descriptor);
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_system_timer() -> Result<(), &'static str> {
+    instantiate_system_timer()?;
+
+    let system_timer_descriptor = generic_driver::DeviceDriverDescriptor::new(
+        SYSTEM_TIMER.assume_init_ref(),
+        None,
+        Some(exception::asynchronous::irq_map::SYSTEM_TIMER_C1),
+    );
+    generic_driver::driver_manager().register_driver(system_timer_descriptor);
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+unsafe fn driver_watchdog() -> Result<(), &'static str> {
+    instantiate_watchdog()?;
+
+    let watchdog_descriptor =
+        generic_driver::DeviceDriverDescriptor::new(WATCHDOG.assume_init_ref(), None, None);
+    generic_driver::driver_manager().register_driver(watchdog_descriptor);
+
+    Ok(())
+}
+
+/// Function needs to ensure that driver registration happens only after correct instantiation.
+#[cfg(feature = "bsp_rpi3")]
+unsafe fn driver_rng() -> Result<(), &'static str> {
+    instantiate_rng()?;
+
+    let rng_descriptor =
+        generic_driver::DeviceDriverDescriptor::new(RNG.assume_init_ref(), None, None);
+    generic_driver::driver_manager().register_driver(rng_descriptor);
+
+    Ok(())
+}
+
 /// Function needs to ensure that driver registration happens only after correct instantiation.
 unsafe fn driver_interrupt_controller() -> Result<(), &'static str> {
     instantiate_interrupt_controller()?;
@@ -173,21 +395,117 @@ pub unsafe fn init() -> Result<(), &'static str> {
     }
 
     driver_uart()?;
+    #[cfg(feature = "console_mini_uart")]
+    driver_mini_uart()?;
     driver_gpio()?;
+    #[cfg(feature = "spi")]
+    driver_spi()?;
+    driver_mailbox()?;
+    #[cfg(feature = "framebuffer")]
+    driver_framebuffer()?;
+    driver_system_timer()?;
+    driver_watchdog()?;
+    #[cfg(feature = "bsp_rpi3")]
+    driver_rng()?;
     driver_interrupt_controller()?;
 
     INIT_DONE.store(true, Ordering::Relaxed);
     Ok(())
 }
 
+/// Return a reference to the GPIO driver.
+///
+/// # Safety
+///
+/// - Must only be called after `init()` was successful.
+pub unsafe fn gpio() -> &'static device_driver::GPIO {
+    GPIO.assume_init_ref()
+}
+
+/// Return a reference to the mailbox driver.
+///
+/// # Safety
+///
+/// - Must only be called after `init()` was successful.
+pub unsafe fn mailbox() -> &'static device_driver::Mailbox {
+    MAILBOX.assume_init_ref()
+}
+
+/// Return a reference to the SPI0 driver.
+///
+/// # Safety
+///
+/// - Must only be called after `init()` was successful.
+#[cfg(feature = "spi")]
+pub unsafe fn spi() -> &'static device_driver::SPI {
+    SPI.assume_init_ref()
+}
+
+/// Return a reference to the framebuffer driver.
+///
+/// # Safety
+///
+/// - Must only be called after `init()` was successful.
+#[cfg(feature = "framebuffer")]
+pub unsafe fn framebuffer() -> &'static device_driver::Framebuffer {
+    FRAMEBUFFER.assume_init_ref()
+}
+
+/// Return a reference to the System Timer driver.
+///
+/// # Safety
+///
+/// - Must only be called after `init()` was successful.
+pub unsafe fn system_timer() -> &'static device_driver::SystemTimer {
+    SYSTEM_TIMER.assume_init_ref()
+}
+
+/// Return a reference to the Watchdog driver.
+///
+/// # Safety
+///
+/// - Must only be called after `init()` was successful.
+pub unsafe fn watchdog() -> &'static device_driver::Watchdog {
+    WATCHDOG.assume_init_ref()
+}
+
+/// Reset the board through the watchdog.
+///
+/// Returns if the watchdog driver was not instantiated yet, so it is safe to call at any time, e.g.
+/// from the panic handler.
+pub fn try_reboot_now() {
+    if WATCHDOG_READY.load(Ordering::Acquire) {
+        unsafe { WATCHDOG.assume_init_ref().reboot_now() }
+    }
+}
+
+/// Return a reference to the RNG driver.
+///
+/// The BCM2711 has a different RNG block, which is not supported yet.
+///
+/// # Safety
+///
+/// - Must only be called after `init()` was successful.
+#[cfg(feature = "bsp_rpi3")]
+pub unsafe fn rng() -> &'static device_driver::RNG {
+    RNG.assume_init_ref()
+}
+
 /// Minimal code needed to bring up the console in QEMU (for testing only). This is often less steps
 /// than on real hardware due to QEMU's abstractions.
 #[cfg(feature = "test_build")]
 pub fn qemu_bring_up_console() {
     use crate::cpu;
 
+    #[cfg(not(feature = "console_mini_uart"))]
     unsafe {
         instantiate_uart().unwrap_or_else(|_| cpu::qemu_exit_failure());
         console::register_console(PL011_UART.assume_init_ref());
     };
+
+    #[cfg(feature = "console_mini_uart")]
+    unsafe {
+        instantiate_mini_uart().unwrap_or_else(|_| cpu::qemu_exit_failure());
+        console::register_console(MINI_UART.assume_init_ref());
+    };
 }
@@ -21,7 +21,16 @@ pub mod irq_map {
     /// The non-secure physical timer IRQ number.
     pub const ARM_NS_PHYSICAL_TIMER: IRQNumber = IRQNumber::Local(LocalIRQ::new(1));
 
+    /// The System Timer compare channel 1 IRQ number.
+    pub const SYSTEM_TIMER_C1: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(1));
+
     pub(in crate::bsp) const PL011_UART: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(57));
+
+    /// The GPIO bank 0 IRQ number.
+    pub const GPIO: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(49));
+
+    /// The EMMC IRQ number.
+    pub const EMMC: IRQNumber = IRQNumber::Peripheral(PeripheralIRQ::new(62));
 }
 
 /// The IRQ map.
@@ -32,5 +41,14 @@ pub mod irq_map {
     /// The non-secure physical timer IRQ number.
     pub const ARM_NS_PHYSICAL_TIMER: IRQNumber = IRQNumber::new(30);
 
+    /// The System Timer compare channel 1 IRQ number.
+    pub const SYSTEM_TIMER_C1: IRQNumber = IRQNumber::new(97);
+
     pub(in crate::bsp) const PL011_UART: IRQNumber = IRQNumber::new(153);
+
+    /// The GPIO bank 0 IRQ number.
+    pub const GPIO: IRQNumber = IRQNumber::new(145);
+
+    /// The EMMC IRQ number.
+    pub const EMMC: IRQNumber = IRQNumber::new(158);
 }
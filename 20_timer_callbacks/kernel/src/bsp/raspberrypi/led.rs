@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! BSP onboard LEDs.
+//!
+//! The green activity (ACT) LED is wired differently on each board:
+//!
+//! - Raspberry Pi 3 Model B: Pin 2 of the GPIO expander, which only the VideoCore firmware can
+//!   reach. It is driven through the mailbox.
+//! - Raspberry Pi 4 Model B: GPIO 42 of the SoC, which is driven directly.
+
+use super::driver;
+#[cfg(feature = "bsp_rpi3")]
+use crate::bsp::device_driver::PropertyBuffer;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Firmware property tag that sets the level of a GPIO expander pin.
+#[cfg(feature = "bsp_rpi3")]
+const TAG_SET_GPIO_STATE: u32 = 0x0003_8041;
+
+/// The firmware numbers the expander pins from 128 on.
+#[cfg(feature = "bsp_rpi3")]
+const ACT_LED_EXPANDER_PIN: u32 = 128 + 2;
+
+#[cfg(feature = "bsp_rpi4")]
+const ACT_LED_GPIO_PIN: u32 = 42;
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(feature = "bsp_rpi3")]
+unsafe fn set_act_led(on: bool) -> Result<(), &'static str> {
+    #[rustfmt::skip]
+    let mut buf = PropertyBuffer([
+        0, 0,
+        TAG_SET_GPIO_STATE, 8, 0, ACT_LED_EXPANDER_PIN, on as u32,
+        PropertyBuffer::<8>::TAG_END,
+    ]);
+
+    driver::mailbox().call(&mut buf)
+}
+
+#[cfg(feature = "bsp_rpi4")]
+unsafe fn set_act_led(on: bool) -> Result<(), &'static str> {
+    let gpio = driver::gpio();
+
+    gpio.set_output_pin(ACT_LED_GPIO_PIN);
+    if on {
+        gpio.set_pin_on(ACT_LED_GPIO_PIN);
+    } else {
+        gpio.set_pin_off(ACT_LED_GPIO_PIN);
+    }
+
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Switch the activity LED on.
+///
+/// # Safety
+///
+/// - Must only be called after `driver::init()` was successful.
+pub unsafe fn act_led_on() -> Result<(), &'static str> {
+    set_act_led(true)
+}
+
+/// Switch the activity LED off.
+///
+/// # Safety
+///
+/// - Must only be called after `driver::init()` was successful.
+pub unsafe fn act_led_off() -> Result<(), &'static str> {
+    set_act_led(false)
+}
@@ -115,15 +115,37 @@ pub(super) mod map {
     pub mod mmio {
         use super::*;
 
+        pub const SYSTEM_TIMER_START:  Address<Physical> = Address::new(0x3F00_3000);
+        pub const SYSTEM_TIMER_SIZE:   usize             =              0x1C;
+
         pub const PERIPHERAL_IC_START: Address<Physical> = Address::new(0x3F00_B200);
         pub const PERIPHERAL_IC_SIZE:  usize             =              0x24;
 
+        pub const MAILBOX_START:       Address<Physical> = Address::new(0x3F00_B880);
+        pub const MAILBOX_SIZE:        usize             =              0x40;
+
+        pub const PM_START:            Address<Physical> = Address::new(0x3F10_0000);
+        pub const PM_SIZE:             usize             =              0x28;
+
+        pub const RNG_START:           Address<Physical> = Address::new(0x3F10_4000);
+        pub const RNG_SIZE:            usize             =              0x14;
+
         pub const GPIO_START:          Address<Physical> = Address::new(0x3F20_0000);
         pub const GPIO_SIZE:           usize             =              0xA0;
 
         pub const PL011_UART_START:    Address<Physical> = Address::new(0x3F20_1000);
         pub const PL011_UART_SIZE:     usize             =              0x48;
 
+        #[cfg(feature = "spi")]
+        pub const SPI0_START:          Address<Physical> = Address::new(0x3F20_4000);
+        #[cfg(feature = "spi")]
+        pub const SPI0_SIZE:           usize             =              0x18;
+
+        #[cfg(feature = "console_mini_uart")]
+        pub const AUX_START:           Address<Physical> = Address::new(0x3F21_5000);
+        #[cfg(feature = "console_mini_uart")]
+        pub const AUX_SIZE:            usize             =              0x6C;
+
         pub const LOCAL_IC_START:      Address<Physical> = Address::new(0x4000_0000);
         pub const LOCAL_IC_SIZE:       usize             =              0x100;
 
@@ -135,19 +157,38 @@ pub(super) mod map {
     pub mod mmio {
         use super::*;
 
-        pub const GPIO_START:       Address<Physical> = Address::new(0xFE20_0000);
-        pub const GPIO_SIZE:        usize             =              0xA0;
+        pub const SYSTEM_TIMER_START: Address<Physical> = Address::new(0xFE00_3000);
+        pub const SYSTEM_TIMER_SIZE:  usize             =              0x1C;
+
+        pub const MAILBOX_START:      Address<Physical> = Address::new(0xFE00_B880);
+        pub const MAILBOX_SIZE:       usize             =              0x40;
+
+        pub const PM_START:           Address<Physical> = Address::new(0xFE10_0000);
+        pub const PM_SIZE:            usize             =              0x28;
+
+        pub const GPIO_START:         Address<Physical> = Address::new(0xFE20_0000);
+        pub const GPIO_SIZE:          usize             =              0xA0;
+
+        pub const PL011_UART_START:   Address<Physical> = Address::new(0xFE20_1000);
+        pub const PL011_UART_SIZE:    usize             =              0x48;
+
+        #[cfg(feature = "spi")]
+        pub const SPI0_START:         Address<Physical> = Address::new(0xFE20_4000);
+        #[cfg(feature = "spi")]
+        pub const SPI0_SIZE:          usize             =              0x18;
 
-        pub const PL011_UART_START: Address<Physical> = Address::new(0xFE20_1000);
-        pub const PL011_UART_SIZE:  usize             =              0x48;
+        #[cfg(feature = "console_mini_uart")]
+        pub const AUX_START:          Address<Physical> = Address::new(0xFE21_5000);
+        #[cfg(feature = "console_mini_uart")]
+        pub const AUX_SIZE:           usize             =              0x6C;
 
-        pub const GICD_START:       Address<Physical> = Address::new(0xFF84_1000);
-        pub const GICD_SIZE:        usize             =              0x824;
+        pub const GICD_START:         Address<Physical> = Address::new(0xFF84_1000);
+        pub const GICD_SIZE:          usize             =              0x824;
 
-        pub const GICC_START:       Address<Physical> = Address::new(0xFF84_2000);
-        pub const GICC_SIZE:        usize             =              0x14;
+        pub const GICC_START:         Address<Physical> = Address::new(0xFF84_2000);
+        pub const GICC_SIZE:          usize             =              0x14;
 
-        pub const END:              Address<Physical> = Address::new(0xFF85_0000);
+        pub const END:                Address<Physical> = Address::new(0xFF85_0000);
     }
 
     pub const END: Address<Physical> = mmio::END;
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! BSP timer facts.
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The frequency in Hz that the architectural timer counter runs at on this board.
+///
+/// Used to sanity-check the value that firmware programmed into the timer.
+#[cfg(feature = "bsp_rpi3")]
+pub const EXPECTED_TIMER_FREQUENCY: u64 = 19_200_000;
+
+/// The frequency in Hz that the architectural timer counter runs at on this board.
+///
+/// Used to sanity-check the value that firmware programmed into the timer.
+#[cfg(feature = "bsp_rpi4")]
+pub const EXPECTED_TIMER_FREQUENCY: u64 = 54_000_000;
+
+/// The frequency in Hz that QEMU's emulated timer counter runs at.
+///
+/// Accepted as well, so that emulated boots do not trip the sanity check.
+pub const QEMU_TIMER_FREQUENCY: u64 = 62_500_000;
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Collections that do not need the heap.
+
+use core::mem::MaybeUninit;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A fixed-capacity FIFO ring buffer.
+///
+/// It does not allocate, so it can be used before the heap is up, e.g. in a `static`. It does not
+/// synchronize either. Wrap it in a `Spinlock` if it is shared with IRQ handlers or other cores.
+pub struct RingBuffer<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+
+    /// Index of the oldest item.
+    head: usize,
+
+    /// Number of initialized items, starting at `head`.
+    len: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        assert!(N > 0);
+
+        Self {
+            // This is safe, because an array of `MaybeUninit` does not need initialization.
+            buf: unsafe { MaybeUninit::uninit().assume_init() },
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Append an item.
+    ///
+    /// If the buffer is full, the oldest item is evicted to make room and returned.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.is_full() { self.pop() } else { None };
+
+        let tail = (self.head + self.len) % N;
+        self.buf[tail].write(item);
+        self.len += 1;
+
+        evicted
+    }
+
+    /// Remove and return the oldest item.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        // This is safe, because all slots from `head` on for `len` items are initialized. The
+        // slot is considered uninitialized again after `head` moved on.
+        let item = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+
+        Some(item)
+    }
+
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return true if the next `push()` will evict an item.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Return the maximum number of items.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Remove all items.
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for RingBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Items come out in the order they went in, also across the wrap-around.
+    #[kernel_test]
+    fn ring_buffer_wraps_around() {
+        let mut rb: RingBuffer<u8, 4> = RingBuffer::new();
+
+        for round in 0..3 {
+            for i in 0..3 {
+                assert_eq!(rb.push(round * 10 + i), None);
+            }
+            assert_eq!(rb.len(), 3);
+
+            for i in 0..3 {
+                assert_eq!(rb.pop(), Some(round * 10 + i));
+            }
+            assert!(rb.is_empty());
+        }
+
+        assert_eq!(rb.pop(), None);
+    }
+
+    /// A push into a full buffer evicts the oldest item.
+    #[kernel_test]
+    fn ring_buffer_overflow_evicts_oldest() {
+        let mut rb: RingBuffer<u8, 4> = RingBuffer::new();
+
+        for i in 0..4 {
+            assert_eq!(rb.push(i), None);
+        }
+        assert!(rb.is_full());
+
+        assert_eq!(rb.push(4), Some(0));
+        assert_eq!(rb.push(5), Some(1));
+        assert!(rb.is_full());
+
+        for i in 2..6 {
+            assert_eq!(rb.pop(), Some(i));
+        }
+        assert!(rb.is_empty());
+    }
+}
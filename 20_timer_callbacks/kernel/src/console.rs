@@ -4,9 +4,14 @@
 
 //! System console.
 
+pub mod ansi;
 mod buffer_console;
+mod hexdump;
 
-use crate::synchronization;
+use crate::{print, synchronization};
+use core::time::Duration;
+
+pub use hexdump::HexDump;
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
@@ -14,7 +19,7 @@ use crate::synchronization;
 
 /// Console interfaces.
 pub mod interface {
-    use core::fmt;
+    use core::{fmt, time::Duration};
 
     /// Console write functions.
     pub trait Write {
@@ -25,17 +30,52 @@ pub mod interface {
         fn write_array(&self, a: &[char]);
 
         /// Write a Rust format string.
+        ///
+        /// Implementations must emit the whole formatted message while holding their IRQ-safe
+        /// lock, so that output from an IRQ handler cannot be spliced into the middle of a line.
         fn write_fmt(&self, args: fmt::Arguments) -> fmt::Result;
 
         /// Block until the last buffered character has been physically put on the TX wire.
+        ///
+        /// This includes characters that are still held back by line-buffered mode.
         fn flush(&self);
+
+        /// Switch line-buffered mode on or off.
+        ///
+        /// In line-buffered mode, formatted output is accumulated and sent out on a newline or
+        /// when the buffer is full. Single characters and arrays are never held back. Consoles
+        /// without buffering support ignore this.
+        fn set_line_buffered(&self, _enable: bool) {}
+
+        /// Make the console usable from the panic handler, whatever state it was left in.
+        ///
+        /// Breaks the lock if it is still held after `lock_timeout`, and sends out and leaves
+        /// line-buffered mode without freeing memory.
+        ///
+        /// # Safety
+        ///
+        /// - Must only be called from the panic handler, with IRQs masked.
+        unsafe fn panic_takeover(&self, _lock_timeout: Duration) {}
+
+        /// Block until the last character is on the wire, without taking the lock.
+        ///
+        /// Meant for the panic handler, after `panic_takeover()`.
+        fn panic_flush(&self) {}
     }
 
     /// Console read functions.
     pub trait Read {
+        /// Read a single character, giving up after `timeout`.
+        ///
+        /// `Duration::MAX` waits forever. Consoles without input support return `None` right away.
+        fn read_char_timeout(&self, _timeout: Duration) -> Option<u8> {
+            None
+        }
+
         /// Read a single character.
         fn read_char(&self) -> char {
-            ' '
+            self.read_char_timeout(Duration::MAX)
+                .map_or(' ', char::from)
         }
 
         /// Clear RX buffers, if any.
@@ -66,6 +106,44 @@ pub mod interface {
 static CUR_CONSOLE: InitStateLock<&'static (dyn interface::All + Sync)> =
     InitStateLock::new(&buffer_console::BUFFER_CONSOLE);
 
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// Line editing, decoupled from the console so that it can be tested with canned input.
+fn readline_with(
+    buf: &mut [u8],
+    mut read: impl FnMut() -> char,
+    mut echo: impl FnMut(&str),
+) -> usize {
+    let mut len = 0;
+
+    loop {
+        match read() {
+            '\r' | '\n' => {
+                echo("\n");
+                return len;
+            }
+            // Backspace and DEL. Erase the last character on the terminal, too.
+            '\x08' | '\x7f' => {
+                if len > 0 {
+                    len -= 1;
+                    echo("\x08 \x08");
+                }
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => {
+                // Drop characters that do not fit anymore, without echoing them.
+                if len < buf.len() {
+                    buf[len] = c as u8;
+                    len += 1;
+                    echo(c.encode_utf8(&mut [0; 4]));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
@@ -85,9 +163,109 @@ pub fn register_console(new_console: &'static (dyn interface::All + Sync)) {
     });
 }
 
+/// Force out all pending output of the current console.
+pub fn flush() {
+    console().flush()
+}
+
+/// Make the current console usable from the panic handler.
+///
+/// # Safety
+///
+/// - See `interface::Write::panic_takeover()`.
+pub unsafe fn panic_takeover(lock_timeout: Duration) {
+    console().panic_takeover(lock_timeout)
+}
+
+/// Wait until the current console sent out everything, without taking its lock.
+pub fn panic_flush() {
+    console().panic_flush()
+}
+
+/// Switch line-buffered mode of the current console on or off.
+pub fn set_line_buffered(enable: bool) {
+    console().set_line_buffered(enable)
+}
+
+/// Read a line from the current console into `buf` and return its length.
+///
+/// Blocks until a carriage return or newline is received, which is not stored in `buf`. Typed
+/// characters are echoed, and backspace erases the last one. Characters that do not fit into `buf`
+/// are dropped. Only printable ASCII is stored.
+pub fn readline(buf: &mut [u8]) -> usize {
+    let con = console();
+
+    readline_with(
+        buf,
+        || con.read_char(),
+        |s| {
+            for c in s.chars() {
+                con.write_char(c)
+            }
+        },
+    )
+}
+
+/// Read a single character from the current console, giving up after `timeout`.
+pub fn read_char_timeout(timeout: Duration) -> Option<u8> {
+    console().read_char_timeout(timeout)
+}
+
+/// Print `bytes` in the classic hex and ASCII layout, 16 bytes per row.
+///
+/// Rows are labeled with addresses starting at `base_addr`.
+pub fn hexdump(bytes: &[u8], base_addr: usize) {
+    print!("{}", HexDump::new(bytes, base_addr));
+}
+
 /// Return a reference to the currently registered console.
 ///
 /// This is the global console used by all printing macros.
 pub fn console() -> &'static dyn interface::All {
     CUR_CONSOLE.read(|con| *con)
 }
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use test_macros::kernel_test;
+
+    fn run(input: &str, buf: &mut [u8]) -> (usize, String) {
+        let mut chars = input.chars();
+        let mut echoed = String::new();
+
+        let len = readline_with(buf, || chars.next().unwrap(), |s| echoed.push_str(s));
+
+        (len, echoed)
+    }
+
+    /// Backspace must erase, and input must stop at the end of the line.
+    #[kernel_test]
+    fn readline_edits_line() {
+        let mut buf = [0_u8; 16];
+
+        let (len, echoed) = run("helo\x08\x7fllo\rignored", &mut buf);
+        assert_eq!(&buf[..len], b"hello");
+        assert_eq!(echoed, "helo\x08 \x08\x08 \x08llo\n");
+
+        // Backspace on an empty line does nothing.
+        let (len, echoed) = run("\x08\n", &mut buf);
+        assert_eq!(len, 0);
+        assert_eq!(echoed, "\n");
+    }
+
+    /// The buffer must not overflow.
+    #[kernel_test]
+    fn readline_respects_buffer_size() {
+        let mut buf = [0_u8; 4];
+
+        let (len, echoed) = run("abcdefg\x08x\n", &mut buf);
+        assert_eq!(&buf[..len], b"abcx");
+        assert_eq!(echoed, "abcd\x08 \x08x\n");
+    }
+}
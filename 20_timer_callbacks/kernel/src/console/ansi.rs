@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! ANSI escape sequences for colors and cursor control.
+//!
+//! Escape sequences are only emitted if enabled with `set_enabled()`. It is off by default, so
+//! that captures of the serial output stay plain text.
+
+use crate::print;
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The standard terminal colors.
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+/// Formats as the sequence that sets the foreground color, or as nothing if disabled.
+pub struct Fg(pub Color);
+
+/// Formats as the sequence that resets all attributes, or as nothing if disabled.
+pub struct Reset;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl fmt::Display for Fg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !is_enabled() {
+            return Ok(());
+        }
+
+        write!(f, "\x1b[{}m", 30 + self.0 as u8)
+    }
+}
+
+impl fmt::Display for Reset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !is_enabled() {
+            return Ok(());
+        }
+
+        f.write_str("\x1b[0m")
+    }
+}
+
+/// Switch emitting of escape sequences on or off.
+pub fn set_enabled(enable: bool) {
+    ENABLED.store(enable, Ordering::Relaxed);
+}
+
+/// Return if escape sequences are emitted.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Set the foreground color of subsequent output.
+pub fn set_color(fg: Color) {
+    print!("{}", Fg(fg));
+}
+
+/// Reset all attributes, including the color.
+pub fn reset() {
+    print!("{}", Reset);
+}
+
+/// Clear the screen and move the cursor to the top left corner.
+pub fn clear_screen() {
+    if is_enabled() {
+        print!("\x1b[2J\x1b[H");
+    }
+}
+
+/// Move the cursor to the given position. Rows and columns start at 1.
+pub fn move_cursor(row: usize, column: usize) {
+    if is_enabled() {
+        print!("\x1b[{};{}H", row, column);
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use test_macros::kernel_test;
+
+    /// Sequences must only be emitted when enabled.
+    #[kernel_test]
+    fn sequences_follow_toggle() {
+        assert!(!is_enabled());
+        assert_eq!(format!("{}x{}", Fg(Color::Red), Reset), "x");
+
+        set_enabled(true);
+        assert_eq!(format!("{}x{}", Fg(Color::Red), Reset), "\x1b[31mx\x1b[0m");
+        assert_eq!(format!("{}", Fg(Color::White)), "\x1b[37m");
+
+        set_enabled(false);
+    }
+}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Hexdump formatting.
+
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const BYTES_PER_ROW: usize = 16;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Formats a byte slice as rows of 16 bytes in hex and ASCII, prefixed by their address.
+pub struct HexDump<'a> {
+    bytes: &'a [u8],
+    base_addr: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<'a> HexDump<'a> {
+    /// Create an instance. `base_addr` is the address printed for the first byte.
+    pub const fn new(bytes: &'a [u8], base_addr: usize) -> Self {
+        Self { bytes, base_addr }
+    }
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.bytes.chunks(BYTES_PER_ROW).enumerate() {
+            write!(f, "{:016x}:", self.base_addr + i * BYTES_PER_ROW)?;
+
+            for col in 0..BYTES_PER_ROW {
+                if col == BYTES_PER_ROW / 2 {
+                    f.write_str(" ")?;
+                }
+
+                // Pad a short last row, so that the ASCII column stays aligned.
+                match row.get(col) {
+                    Some(b) => write!(f, " {:02x}", b)?,
+                    None => f.write_str("   ")?,
+                }
+            }
+
+            f.write_str("  |")?;
+            for &b in row {
+                let c = if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                };
+
+                write!(f, "{}", c)?;
+            }
+            f.write_str("|\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use test_macros::kernel_test;
+
+    /// Check the layout of a full row, a short row and non-printable bytes.
+    #[kernel_test]
+    fn hexdump_layout() {
+        let mut bytes = [0_u8; 20];
+        bytes[..16].copy_from_slice(b"Hello, world!\x00\x01\x7f");
+        bytes[16..].copy_from_slice(&[0x55, 0xaa, b'A', b' ']);
+
+        let expected = "\
+0000000000001000: 48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 00 01 7f  |Hello, world!...|
+0000000000001010: 55 aa 41 20                                       |U.A |
+";
+
+        assert_eq!(HexDump::new(&bytes, 0x1000).to_string(), expected);
+        assert_eq!(HexDump::new(&[], 0x1000).to_string(), "");
+    }
+}
@@ -10,12 +10,25 @@ mod arch_cpu;
 
 mod boot;
 
+pub mod per_core;
 pub mod smp;
 
 //--------------------------------------------------------------------------------------------------
 // Architectural Public Reexports
 //--------------------------------------------------------------------------------------------------
-pub use arch_cpu::{nop, wait_forever};
+pub use arch_cpu::{nop, send_event, wait_for_event, wait_for_interrupt, wait_forever};
 
 #[cfg(feature = "test_build")]
 pub use arch_cpu::{qemu_exit_failure, qemu_exit_success};
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Return the id of the executing core.
+///
+/// Ids range from zero to `bsp::cpu::NUM_CORES - 1`.
+#[inline(always)]
+pub fn this_core_id() -> usize {
+    smp::core_id()
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Per-core storage.
+//!
+//! Data that every core needs its own copy of, like statistics or queues, can live in a
+//! [`PerCore`]. Each core only touches its own instance through [`PerCore::get()`], so the cores
+//! never contend for it.
+//!
+//! Other code on the same core, namely IRQ handlers, can still interrupt an access. Wrapping the
+//! data in an `IRQSafeNullLock` covers that, and is sound here because no other core accesses the
+//! data.
+
+use super::this_core_id;
+use crate::bsp;
+use core::slice::Iter;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// One instance of `T` per core.
+pub struct PerCore<T> {
+    values: [T; bsp::cpu::NUM_CORES],
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<T> PerCore<T> {
+    /// Create an instance from the initial value of each core, indexed by core id.
+    pub const fn new(values: [T; bsp::cpu::NUM_CORES]) -> Self {
+        Self { values }
+    }
+
+    /// Return the instance of the executing core.
+    ///
+    /// Must only be called after the MMU is on and the core runs on its own stack, i.e. from
+    /// `kernel_init()` or a secondary core's entry function onwards.
+    #[inline(always)]
+    pub fn get(&self) -> &T {
+        &self.values[this_core_id()]
+    }
+
+    /// Return the instances of all cores, e.g. to sum up statistics.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.values.iter()
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use test_macros::kernel_test;
+
+    /// The executing core must get the instance at its id, and no other.
+    #[kernel_test]
+    fn per_core_returns_own_instance() {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicUsize = AtomicUsize::new(0);
+        let counters = PerCore::new([ZERO; bsp::cpu::NUM_CORES]);
+
+        counters.get().fetch_add(1, Ordering::Relaxed);
+
+        for (core, counter) in counters.iter().enumerate() {
+            let expected = usize::from(core == this_core_id());
+
+            assert_eq!(counter.load(Ordering::Relaxed), expected);
+        }
+    }
+}
@@ -3,12 +3,85 @@
 // Copyright (c) 2018-2022 Andre Richter <andre.o.richter@gmail.com>
 
 //! Symmetric multiprocessing.
+//!
+//! Only the boot core runs `kernel_init()`. Secondary cores stay parked by the firmware until
+//! `start_core()` releases them. A released core switches to EL1 with the kernel's translation
+//! tables, installs the exception vectors and calls its entry function with IRQs masked.
 
 #[cfg(target_arch = "aarch64")]
 #[path = "../_arch/aarch64/cpu/smp.rs"]
 mod arch_smp;
 
+use crate::{bsp, memory::Address, state};
+use alloc::alloc::{alloc, Layout};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Size of the stack that is allocated for each secondary core.
+const SECONDARY_CORE_STACK_SIZE: usize = 64 * 1024;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+/// Bitmask of the cores that were released.
+static STARTED_CORES: AtomicU8 = AtomicU8::new(0);
+
 //--------------------------------------------------------------------------------------------------
 // Architectural Public Reexports
 //--------------------------------------------------------------------------------------------------
-pub use arch_smp::core_id;
+pub use arch_smp::{core_id, mpidr};
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Start a secondary core, which then executes `entry`.
+///
+/// Returns once the core runs kernel code. The first successful start transitions the kernel to
+/// multi-core main state.
+///
+/// Must only be called from the boot core.
+pub fn start_core(core: usize, entry: fn() -> !) -> Result<(), &'static str> {
+    if core_id::<usize>() != bsp::cpu::BOOT_CORE_ID as usize {
+        return Err("Secondary cores can only be started from the boot core");
+    }
+
+    if state::state_manager().is_init() {
+        return Err("Secondary cores can only be started after kernel init");
+    }
+
+    let release_addr =
+        bsp::cpu::secondary_core_release_addr(core).ok_or("No such secondary core")?;
+
+    if STARTED_CORES.load(Ordering::Relaxed) & (1 << core) != 0 {
+        return Err("Core already started");
+    }
+
+    let layout = Layout::from_size_align(SECONDARY_CORE_STACK_SIZE, 16).unwrap();
+    let stack = unsafe { alloc(layout) };
+    if stack.is_null() {
+        return Err("Out of memory for the core's stack");
+    }
+
+    if !state::state_manager().is_multi_core_main() {
+        state::state_manager().transition_to_multi_core_main();
+    }
+
+    // On failure, the stack is deliberately leaked. The core might still come up late.
+    unsafe {
+        arch_smp::start_core(
+            release_addr,
+            Address::new(stack as usize),
+            SECONDARY_CORE_STACK_SIZE,
+            entry,
+        )?;
+    }
+
+    STARTED_CORES.fetch_or(1 << core, Ordering::Relaxed);
+
+    Ok(())
+}
@@ -7,6 +7,7 @@
 use crate::{
     exception, info,
     synchronization::{interface::ReadWriteEx, InitStateLock},
+    warn,
 };
 use alloc::vec::Vec;
 use core::fmt;
@@ -34,6 +35,15 @@ pub mod interface {
             Ok(())
         }
 
+        /// Called by the kernel after init to check that the device is present and responding.
+        ///
+        /// A driver that registered and initialized fine might still talk to absent or mis-mapped
+        /// hardware. Drivers should do a cheap plausibility check here, e.g. read back a register
+        /// with known content.
+        fn probe(&self) -> Result<(), &'static str> {
+            Ok(())
+        }
+
         /// Called by the kernel to register and enable the device's IRQ handler.
         ///
         /// Rust's type system will prevent a call to this function unless the calling instance
@@ -164,14 +174,36 @@ where
                     }
                 }
             }
+
+            // 4. Probe the hardware. A failed probe is not fatal, because the device might not be
+            //    needed, so only report it.
+            for descriptor in descriptors {
+                if let Err(x) = descriptor.device_driver.probe() {
+                    warn!(
+                        "Driver probe failed: {}: {}",
+                        descriptor.device_driver.compatible(),
+                        x
+                    );
+                }
+            }
         })
     }
 
     /// Enumerate all registered device drivers.
+    ///
+    /// Each driver is probed again, and failures are shown next to the compatible string.
     pub fn enumerate(&self) {
         self.descriptors.read(|descriptors| {
             for (i, desc) in descriptors.iter().enumerate() {
-                info!("      {}. {}", i + 1, desc.device_driver.compatible());
+                match desc.device_driver.probe() {
+                    Ok(()) => info!("      {}. {}", i + 1, desc.device_driver.compatible()),
+                    Err(x) => info!(
+                        "      {}. {} - probe failed: {}",
+                        i + 1,
+                        desc.device_driver.compatible(),
+                        x
+                    ),
+                }
             }
         });
     }
@@ -9,8 +9,12 @@
 mod arch_asynchronous;
 mod null_irq_manager;
 
-use crate::{bsp, synchronization};
-use core::marker::PhantomData;
+use crate::{bsp, cpu::per_core::PerCore, synchronization};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 //--------------------------------------------------------------------------------------------------
 // Architectural Public Reexports
@@ -20,6 +24,13 @@ pub use arch_asynchronous::{
     print_state,
 };
 
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Adapts a closure to the `IRQHandler` interface.
+struct ClosureIRQHandler(IRQHandlerClosure);
+
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
@@ -43,6 +54,17 @@ where
     handler: &'static (dyn interface::IRQHandler + Sync),
 }
 
+/// The closure type accepted by `IRQManager::register_handler_fn()`.
+pub type IRQHandlerClosure = Box<dyn Fn() + Send + Sync>;
+
+/// Number of dispatches per IRQ, for use by IRQ managers.
+///
+/// Each core counts in its own instance, so that the cores do not contend. Sized during kernel
+/// init, updated lock-free afterwards.
+pub struct IRQCounts {
+    counts: PerCore<InitStateLock<Vec<AtomicU64>>>,
+}
+
 /// IRQContext token.
 ///
 /// An instance of this type indicates that the local core is currently executing in IRQ
@@ -78,6 +100,24 @@ pub mod interface {
             irq_handler_descriptor: super::IRQHandlerDescriptor<Self::IRQNumberType>,
         ) -> Result<(), &'static str>;
 
+        /// Register a closure as the handler of an IRQ.
+        ///
+        /// This is for kernel code that wants to react to an IRQ without implementing a whole
+        /// device driver. It can be called after kernel init, too. Like with `register_handler()`,
+        /// only one handler per IRQ is accepted, and the IRQ must be enabled separately.
+        ///
+        /// Handlers cannot be unregistered, so the closure lives until the system shuts down.
+        fn register_handler_fn(
+            &self,
+            irq_number: Self::IRQNumberType,
+            handler: super::IRQHandlerClosure,
+        ) -> Result<(), &'static str> {
+            let handler = super::Box::leak(super::Box::new(super::ClosureIRQHandler(handler)));
+            let descriptor = super::IRQHandlerDescriptor::new(irq_number, "Closure", handler);
+
+            self.register_handler(descriptor)
+        }
+
         /// Enable an interrupt in the controller.
         fn enable(&self, irq_number: &Self::IRQNumberType);
 
@@ -94,6 +134,11 @@ pub mod interface {
             ic: &super::IRQContext<'irq_context>,
         );
 
+        /// Return how often the handler of an IRQ has been dispatched.
+        fn count(&self, _irq_number: &Self::IRQNumberType) -> u64 {
+            0
+        }
+
         /// Print list of registered handlers.
         fn print_handler(&self) {}
     }
@@ -107,6 +152,18 @@ static CUR_IRQ_MANAGER: InitStateLock<
     &'static (dyn interface::IRQManager<IRQNumberType = IRQNumber> + Sync),
 > = InitStateLock::new(&null_irq_manager::NULL_IRQ_MANAGER);
 
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+impl interface::IRQHandler for ClosureIRQHandler {
+    fn handle(&self) -> Result<(), &'static str> {
+        (self.0)();
+
+        Ok(())
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
@@ -145,6 +202,41 @@ where
     }
 }
 
+impl IRQCounts {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        // Array repeat expressions need a constant for types that are not `Copy`.
+        #[allow(clippy::declare_interior_mutable_const)]
+        const NO_COUNTS: InitStateLock<Vec<AtomicU64>> = InitStateLock::new(Vec::new());
+
+        Self {
+            counts: PerCore::new([NO_COUNTS; bsp::cpu::NUM_CORES]),
+        }
+    }
+
+    /// Make room for IRQ numbers up to `max_inclusive`. Must be called during kernel init.
+    pub fn init(&self, max_inclusive: usize) {
+        for counts in self.counts.iter() {
+            counts.write(|counts| counts.resize_with(max_inclusive + 1, || AtomicU64::new(0)));
+        }
+    }
+
+    /// Count a dispatch of `irq_number` on the executing core.
+    pub fn increment(&self, irq_number: usize) {
+        self.counts
+            .get()
+            .read(|counts| counts[irq_number].fetch_add(1, Ordering::Relaxed));
+    }
+
+    /// Return the dispatches of `irq_number`, summed up over all cores.
+    pub fn get(&self, irq_number: usize) -> u64 {
+        self.counts
+            .iter()
+            .map(|counts| counts.read(|counts| counts[irq_number].load(Ordering::Relaxed)))
+            .sum()
+    }
+}
+
 impl<'irq_context> IRQContext<'irq_context> {
     /// Creates an IRQContext token.
     ///
@@ -134,20 +134,23 @@
 extern crate alloc;
 
 mod panic_wait;
-mod synchronization;
 
 pub mod backtrace;
 pub mod bsp;
+pub mod collections;
 pub mod common;
 pub mod console;
 pub mod cpu;
 pub mod driver;
 pub mod exception;
+pub mod log;
 pub mod memory;
 pub mod print;
 pub mod state;
 pub mod symbols;
+pub mod synchronization;
 pub mod time;
+pub mod work_queue;
 
 //--------------------------------------------------------------------------------------------------
 // Public Code
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Log levels.
+//!
+//! The logging macros `error!`, `warn!`, `info!`, `debug!` and `trace!` only print if their level
+//! is at or above the threshold set with `set_max_level()`. Plain `print!` and `println!`, and
+//! therefore panic messages, are never filtered.
+//!
+//! Log messages that pass the filter are also kept in an in-memory ring buffer, which can be
+//! replayed with `dump_ring()`. With the `panic_log_replay` feature, the panic handler does so, so
+//! that the history before a crash is not lost.
+
+mod ring;
+
+use crate::{
+    print,
+    synchronization::{interface::Mutex, Spinlock},
+};
+use core::{
+    fmt::{self, Write},
+    sync::atomic::{AtomicU8, Ordering},
+    time::Duration,
+};
+use ring::LogRing;
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const LOG_RING_SIZE: usize = 8 * 1024;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Log levels, from most to least severe.
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(if cfg!(feature = "debug_prints") {
+    Level::Debug as u8
+} else {
+    Level::Info as u8
+});
+
+static LOG_RING: Spinlock<LogRing<LOG_RING_SIZE>> = Spinlock::new(LogRing::new());
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Set the least severe level that is still printed.
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Return the least severe level that is still printed.
+pub fn max_level() -> Level {
+    match MAX_LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Error,
+        1 => Level::Warn,
+        2 => Level::Info,
+        3 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+/// Return if messages of the given level are printed.
+#[inline(always)]
+pub fn enabled(level: Level) -> bool {
+    (level as u8) <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Store a formatted log message in the ring buffer.
+///
+/// Can be called from interrupt context.
+#[doc(hidden)]
+pub fn _record(args: fmt::Arguments) {
+    LOG_RING.lock(|ring| ring.write_fmt(args)).unwrap();
+}
+
+/// Make the ring buffer usable from the panic handler, even if the panic happened while it was
+/// locked.
+///
+/// # Safety
+///
+/// - Must only be called from the panic handler, with IRQs masked.
+pub unsafe fn panic_takeover(lock_timeout: Duration) {
+    LOG_RING.force_unlock(lock_timeout);
+}
+
+/// Print the log messages that are still held in the ring buffer, oldest first.
+pub fn dump_ring() {
+    LOG_RING.lock(|ring| {
+        print!(
+            "----- Recent log messages -----\n{}----- End of recent log messages -----\n",
+            ring
+        )
+    });
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Levels at or above the threshold must be enabled.
+    #[kernel_test]
+    fn threshold_is_applied() {
+        let old = max_level();
+
+        set_max_level(Level::Warn);
+        assert_eq!(max_level(), Level::Warn);
+        assert!(enabled(Level::Error));
+        assert!(enabled(Level::Warn));
+        assert!(!enabled(Level::Info));
+        assert!(!enabled(Level::Trace));
+
+        set_max_level(Level::Trace);
+        assert!(enabled(Level::Trace));
+
+        set_max_level(old);
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! A byte ring buffer that keeps the most recent log output.
+
+use core::fmt;
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// A ring buffer that overwrites the oldest bytes when full.
+pub struct LogRing<const SIZE: usize> {
+    buf: [u8; SIZE],
+    write_ptr: usize,
+    wrapped: bool,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<const SIZE: usize> LogRing<SIZE> {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; SIZE],
+            write_ptr: 0,
+            wrapped: false,
+        }
+    }
+
+    /// Append bytes, overwriting the oldest ones if needed.
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.buf[self.write_ptr] = b;
+            self.write_ptr += 1;
+
+            if self.write_ptr == SIZE {
+                self.write_ptr = 0;
+                self.wrapped = true;
+            }
+        }
+    }
+
+    /// Iterate over the stored bytes, oldest first.
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        let (newer, older) = self.buf.split_at(self.write_ptr);
+        let older = if self.wrapped { older } else { &[] };
+
+        older.iter().chain(newer).copied()
+    }
+}
+
+impl<const SIZE: usize> fmt::Write for LogRing<SIZE> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_bytes(s.as_bytes());
+
+        Ok(())
+    }
+}
+
+impl<const SIZE: usize> fmt::Display for LogRing<SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+
+        // After wrapping, the oldest line is most likely cut. Start at the next full line.
+        let mut bytes = self.bytes().peekable();
+        if self.wrapped {
+            while bytes.next_if(|&b| b != b'\n').is_some() {}
+            bytes.next();
+        }
+
+        // Multi-byte characters might have been cut, too. Log output is ASCII anyways.
+        for b in bytes {
+            f.write_char(if b.is_ascii() { b as char } else { '?' })?;
+        }
+
+        Ok(())
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, string::ToString};
+    use core::fmt::Write;
+    use test_macros::kernel_test;
+
+    /// Content must be replayed in order, and only the oldest bytes are dropped.
+    #[kernel_test]
+    fn log_ring_keeps_newest() {
+        let mut ring: LogRing<16> = LogRing::new();
+
+        write!(ring, "one\ntwo\n").unwrap();
+        assert_eq!(ring.to_string(), "one\ntwo\n");
+
+        write!(ring, "three\nfour\nxy\n").unwrap();
+        assert_eq!(ring.bytes().count(), 16);
+
+        // "one\ntw" was overwritten, the remainder of the cut line is skipped.
+        assert_eq!(format!("{}", ring), "three\nfour\nxy\n");
+    }
+}
@@ -10,12 +10,17 @@
 //! The `kernel` binary.
 
 #![feature(format_args_nl)]
+#![feature(linkage)]
 #![no_main]
 #![no_std]
 
 extern crate alloc;
 
-use libkernel::{bsp, cpu, driver, exception, info, memory, state, time};
+use core::sync::atomic::{AtomicBool, Ordering};
+use libkernel::{bsp, cpu, driver, exception, info, memory, state, time, warn, work_queue};
+
+/// Set by core 1 once it reported in.
+static CORE_1_REPORTED: AtomicBool = AtomicBool::new(false);
 
 /// Early init code.
 ///
@@ -55,11 +60,53 @@ unsafe fn kernel_init() -> ! {
     kernel_main()
 }
 
-/// The main function running after the early init.
-fn kernel_main() -> ! {
+/// The application entry point, called by `kernel_main()` once all subsystems are up.
+///
+/// It is linked weakly, so that an application linked into the kernel can provide its own
+/// `app_main()`. The default implementation runs the timer callback demo and echoes console input.
+/// An own implementation must call `work_queue::run_pending_work()` regularly, and should use
+/// `cpu::wait_for_interrupt()` to idle.
+#[linkage = "weak"]
+#[no_mangle]
+fn app_main() -> ! {
     use alloc::boxed::Box;
     use core::time::Duration;
 
+    time::time_manager().set_timeout_once(Duration::from_secs(5), Box::new(|| info!("Once 5")));
+    time::time_manager().set_timeout_once(Duration::from_secs(3), Box::new(|| info!("Once 2")));
+    time::time_manager()
+        .set_timeout_periodic(Duration::from_secs(1), Box::new(|| info!("Periodic 1 sec")));
+
+    info!("Echoing input now");
+
+    // Idle loop. Work that IRQ handlers deferred runs here, outside of interrupt context.
+    loop {
+        work_queue::run_pending_work();
+
+        // Sleep until the next IRQ. Checking with IRQs masked ensures that work scheduled right
+        // before `wfi` does not wait for yet another IRQ. A pending IRQ still ends the sleep.
+        exception::asynchronous::exec_with_irq_masked(|| {
+            if !work_queue::has_pending_work() {
+                cpu::wait_for_interrupt();
+            }
+        });
+    }
+}
+
+/// Entry of core 1.
+fn core_1_main() -> ! {
+    info!(
+        "      Core {} online, MPIDR_EL1: {:#x}",
+        cpu::smp::core_id::<usize>(),
+        cpu::smp::mpidr()
+    );
+    CORE_1_REPORTED.store(true, Ordering::Release);
+
+    cpu::wait_forever()
+}
+
+/// The main function running after the early init.
+fn kernel_main() -> ! {
     info!("{}", libkernel::version());
     info!("Booting on: {}", bsp::board_name());
 
@@ -86,11 +133,16 @@ fn kernel_main() -> ! {
     info!("Kernel heap:");
     memory::heap_alloc::kernel_heap_allocator().print_usage();
 
-    time::time_manager().set_timeout_once(Duration::from_secs(5), Box::new(|| info!("Once 5")));
-    time::time_manager().set_timeout_once(Duration::from_secs(3), Box::new(|| info!("Once 2")));
-    time::time_manager()
-        .set_timeout_periodic(Duration::from_secs(1), Box::new(|| info!("Periodic 1 sec")));
+    info!("Starting secondary cores:");
+    match cpu::smp::start_core(1, core_1_main) {
+        Ok(()) => {
+            // Let core 1 finish printing first, so that the output does not interleave.
+            while !CORE_1_REPORTED.load(Ordering::Acquire) {
+                cpu::nop();
+            }
+        }
+        Err(x) => warn!("Could not start core 1: {}", x),
+    }
 
-    info!("Echoing input now");
-    cpu::wait_forever();
+    app_main()
 }
@@ -4,6 +4,7 @@
 
 //! Memory Management.
 
+pub mod cache;
 pub mod heap_alloc;
 pub mod mmu;
 
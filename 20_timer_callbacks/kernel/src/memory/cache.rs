@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Cache maintenance.
+//!
+//! Devices that access memory via DMA do not see the CPU's caches. Memory shared with such devices
+//! must be cleaned before the device reads it, and invalidated before the CPU reads what the device
+//! wrote.
+
+#[cfg(target_arch = "aarch64")]
+#[path = "../_arch/aarch64/memory/cache.rs"]
+mod arch_cache;
+
+//--------------------------------------------------------------------------------------------------
+// Architectural Public Reexports
+//--------------------------------------------------------------------------------------------------
+pub use arch_cache::{clean_and_invalidate_range, clean_range, dcache_line_size, invalidate_range};
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{Address, Virtual};
+    use test_macros::kernel_test;
+
+    /// The line size must be sane.
+    #[kernel_test]
+    fn dcache_line_size_is_sane() {
+        let line_size = dcache_line_size();
+
+        assert!(line_size.is_power_of_two());
+        assert!((16..=2048).contains(&line_size));
+    }
+
+    /// Maintenance must not change what the CPU sees, also for unaligned ranges.
+    #[kernel_test]
+    fn maintenance_preserves_data() {
+        let mut buf = [0_u8; 256];
+        for (i, x) in buf.iter_mut().enumerate() {
+            *x = i as u8;
+        }
+
+        let start = Address::<Virtual>::new(buf.as_ptr() as usize + 3);
+        let len = 200;
+
+        clean_range(start, len);
+        clean_and_invalidate_range(start, len);
+
+        // Cleaned before, so invalidation does not lose anything.
+        invalidate_range(start, len);
+
+        for (i, x) in buf.iter().enumerate() {
+            assert_eq!(*x, i as u8);
+        }
+    }
+}
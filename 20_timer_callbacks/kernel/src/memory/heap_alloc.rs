@@ -8,11 +8,11 @@ use crate::{
     backtrace, bsp, common, debug, info,
     memory::{Address, Virtual},
     synchronization,
-    synchronization::IRQSafeNullLock,
+    synchronization::Spinlock,
     warn,
 };
 use alloc::alloc::{GlobalAlloc, Layout};
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use linked_list_allocator::Heap as LinkedListHeap;
 
 //--------------------------------------------------------------------------------------------------
@@ -21,7 +21,29 @@ use linked_list_allocator::Heap as LinkedListHeap;
 
 /// A heap allocator that can be lazyily initialized.
 pub struct HeapAllocator {
-    inner: IRQSafeNullLock<LinkedListHeap>,
+    inner: Spinlock<LinkedListHeap>,
+
+    // Atomics instead of lock-protected data, so that they can be updated from within the
+    // allocator's own lock.
+    peak_used: AtomicUsize,
+    num_allocs: AtomicUsize,
+    num_frees: AtomicUsize,
+}
+
+/// Heap usage statistics.
+#[derive(Copy, Clone, Debug)]
+pub struct HeapStats {
+    /// Bytes currently allocated.
+    pub used: usize,
+
+    /// The maximum of `used` since boot.
+    pub peak_used: usize,
+
+    /// Number of successful allocations since boot.
+    pub num_allocs: usize,
+
+    /// Number of deallocations since boot.
+    pub num_frees: usize,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -76,7 +98,25 @@ impl HeapAllocator {
     /// Create an instance.
     pub const fn new() -> Self {
         Self {
-            inner: IRQSafeNullLock::new(LinkedListHeap::empty()),
+            inner: Spinlock::new(LinkedListHeap::empty()),
+            peak_used: AtomicUsize::new(0),
+            num_allocs: AtomicUsize::new(0),
+            num_frees: AtomicUsize::new(0),
+        }
+    }
+
+    /// Return the number of bytes currently allocated.
+    pub fn used(&self) -> usize {
+        self.inner.lock(|inner| inner.used())
+    }
+
+    /// Return the heap usage statistics.
+    pub fn stats(&self) -> HeapStats {
+        HeapStats {
+            used: self.used(),
+            peak_used: self.peak_used.load(Ordering::Relaxed),
+            num_allocs: self.num_allocs.load(Ordering::Relaxed),
+            num_frees: self.num_frees.load(Ordering::Relaxed),
         }
     }
 
@@ -99,14 +139,29 @@ impl HeapAllocator {
         } else {
             info!("      Free: {} Byte", free);
         }
+
+        let stats = self.stats();
+        info!(
+            "      Peak: {} Byte, allocs: {}, frees: {}",
+            stats.peak_used, stats.num_allocs, stats.num_frees
+        );
     }
 }
 
 unsafe impl GlobalAlloc for HeapAllocator {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let result = KERNEL_HEAP_ALLOCATOR
-            .inner
-            .lock(|inner| inner.allocate_first_fit(layout).ok());
+        let result = KERNEL_HEAP_ALLOCATOR.inner.lock(|inner| {
+            let allocation = inner.allocate_first_fit(layout).ok()?;
+
+            KERNEL_HEAP_ALLOCATOR
+                .num_allocs
+                .fetch_add(1, Ordering::Relaxed);
+            KERNEL_HEAP_ALLOCATOR
+                .peak_used
+                .fetch_max(inner.used(), Ordering::Relaxed);
+
+            Some(allocation)
+        });
 
         match result {
             None => core::ptr::null_mut(),
@@ -124,6 +179,9 @@ unsafe impl GlobalAlloc for HeapAllocator {
         KERNEL_HEAP_ALLOCATOR
             .inner
             .lock(|inner| inner.deallocate(core::ptr::NonNull::new_unchecked(ptr), layout));
+        KERNEL_HEAP_ALLOCATOR
+            .num_frees
+            .fetch_add(1, Ordering::Relaxed);
 
         debug_print_alloc_dealloc("Free", ptr, layout);
     }
@@ -145,3 +203,34 @@ pub fn kernel_init_heap_allocator() {
 
     INIT_DONE.store(true, Ordering::Relaxed);
 }
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use test_macros::kernel_test;
+
+    /// Statistics must follow allocations and deallocations.
+    #[kernel_test]
+    fn heap_stats_are_tracked() {
+        let before = kernel_heap_allocator().stats();
+
+        // Touch the allocation, so that it can't be optimized out.
+        let mut v: Vec<u8> = Vec::with_capacity(4096);
+        unsafe { core::ptr::write_volatile(v.as_mut_ptr(), 0) };
+        let during = kernel_heap_allocator().stats();
+        drop(v);
+        let after = kernel_heap_allocator().stats();
+
+        assert_eq!(during.num_allocs, before.num_allocs + 1);
+        assert!(during.peak_used >= before.used + 4096);
+
+        assert_eq!(after.num_frees, before.num_frees + 1);
+        assert_eq!(after.used, before.used);
+        assert_eq!(after.peak_used, during.peak_used);
+    }
+}
@@ -16,6 +16,7 @@ mod types;
 use crate::{
     bsp,
     memory::{Address, Physical, Virtual},
+    state,
     synchronization::{self, interface::Mutex},
 };
 use core::{fmt, num::NonZeroUsize};
@@ -224,6 +225,53 @@ pub unsafe fn kernel_map_mmio(
     Ok(virt_addr + offset_into_start_page)
 }
 
+/// Remove an MMIO mapping that was established by `kernel_map_mmio()`.
+///
+/// `virt_addr` and `size` describe the device's MMIO region, with `virt_addr` as returned by
+/// `kernel_map_mmio()`. If the mapping is shared with other drivers, it stays in place until the
+/// last user is gone.
+///
+/// Only possible during kernel init, like mapping. Afterwards, the translation tables and the
+/// mapping record are read-only, and an error is returned.
+///
+/// # Safety
+///
+/// - The caller must ensure that the region is not accessed anymore afterwards.
+pub unsafe fn kernel_unmap_mmio(
+    virt_addr: Address<Virtual>,
+    size: usize,
+) -> Result<(), &'static str> {
+    if !state::state_manager().is_init() {
+        return Err("MMIO can only be unmapped during kernel init");
+    }
+
+    let virt_region = MemoryRegion::new(
+        PageAddress::from(virt_addr.align_down_page()),
+        PageAddress::from((virt_addr + size).align_up_page()),
+    );
+
+    if virt_region.num_pages() == 0 {
+        return Err("Requested 0 pages");
+    }
+
+    if !mapping_record::kernel_remove_mmio_user(&virt_region)? {
+        return Ok(());
+    }
+
+    bsp::memory::mmu::kernel_translation_tables().write(|tables| tables.unmap_at(&virt_region))?;
+
+    page_alloc::kernel_mmio_va_allocator().lock(|allocator| allocator.free(&virt_region));
+
+    Ok(())
+}
+
+/// Translate a kernel virtual address to a physical address.
+///
+/// Walks the kernel's translation tables. Returns `None` if the address is not mapped.
+pub fn virt_to_phys(virt_addr: Address<Virtual>) -> Option<Address<Physical>> {
+    try_kernel_virt_addr_to_phys_addr(virt_addr).ok()
+}
+
 /// Try to translate a kernel virtual page address to a physical page address.
 ///
 /// Will only succeed if there exists a valid mapping for the input page.
@@ -244,6 +292,17 @@ pub fn try_kernel_page_attributes(
         .read(|tables| tables.try_page_attributes(virt_page_addr))
 }
 
+/// Return the name and region of the recorded kernel mapping closest to `virt_addr`, if it is at
+/// most `max_distance` bytes away.
+///
+/// Used for fault diagnostics.
+pub fn kernel_find_nearby_mapping(
+    virt_addr: Address<Virtual>,
+    max_distance: usize,
+) -> Option<(&'static str, MemoryRegion<Virtual>)> {
+    mapping_record::kernel_find_nearby(virt_addr, max_distance)
+}
+
 /// Human-readable print of all recorded kernel mappings.
 pub fn kernel_print_mappings() {
     mapping_record::kernel_print()
@@ -260,3 +319,31 @@ pub unsafe fn enable_mmu_and_caching(
 ) -> Result<(), MMUEnableError> {
     arch_mmu::mmu().enable_mmu_and_caching(phys_tables_base_addr)
 }
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// Translation must yield the physical address a region was mapped to.
+    #[kernel_test]
+    fn virt_to_phys_round_trip() {
+        let phys_addr = Address::<Physical>::new(0x1000_0040);
+        let mmio_descriptor = MMIODescriptor::new(phys_addr, 0x100);
+
+        let virt_addr = unsafe { kernel_map_mmio("Test", &mmio_descriptor).unwrap() };
+        assert_eq!(virt_to_phys(virt_addr), Some(phys_addr));
+        assert_eq!(virt_to_phys(virt_addr + 0x80), Some(phys_addr + 0x80));
+
+        // Kernel code is mapped, too.
+        let code_addr = Address::<Virtual>::new(virt_to_phys as usize);
+        assert!(virt_to_phys(code_addr).is_some());
+
+        // The kernel's tables only cover the top of the address space.
+        assert_eq!(virt_to_phys(Address::new(0x1000)), None);
+    }
+}
@@ -6,7 +6,7 @@
 
 use super::{
     AccessPermissions, Address, AttributeFields, MMIODescriptor, MemAttributes, MemoryRegion,
-    Physical, Virtual,
+    PageAddress, Physical, Virtual,
 };
 use crate::{bsp, common, info, synchronization, synchronization::InitStateLock};
 use alloc::{vec, vec::Vec};
@@ -59,6 +59,15 @@ impl MappingRecordEntry {
     pub fn add_user(&mut self, user: &'static str) {
         self.users.push(user);
     }
+
+    fn virt_region(&self) -> MemoryRegion<Virtual> {
+        let start = PageAddress::from(self.virt_start_addr);
+
+        MemoryRegion::new(
+            start,
+            start.checked_offset(self.num_pages as isize).unwrap(),
+        )
+    }
 }
 
 impl MappingRecord {
@@ -109,6 +118,61 @@ impl MappingRecord {
         self.sort();
     }
 
+    /// Return the entry closest to the given address, if it is at most `max_distance` bytes away.
+    fn find_nearby(
+        &self,
+        virt_addr: Address<Virtual>,
+        max_distance: usize,
+    ) -> Option<&MappingRecordEntry> {
+        let addr = virt_addr.as_usize();
+
+        let distance = |x: &MappingRecordEntry| {
+            let start = x.virt_start_addr.as_usize();
+            let size = x.num_pages * bsp::memory::mmu::KernelGranule::SIZE;
+            let end_inclusive = start + (size - 1);
+
+            if addr < start {
+                start - addr
+            } else if addr > end_inclusive {
+                addr - end_inclusive
+            } else {
+                0
+            }
+        };
+
+        self.inner
+            .iter()
+            .filter(|x| distance(x) <= max_distance)
+            .min_by_key(|x| distance(x))
+    }
+
+    /// Drop the most recently added user of an MMIO region.
+    ///
+    /// Returns true if the region has no users left, in which case its entry was removed.
+    pub fn remove_mmio_user(
+        &mut self,
+        virt_region: &MemoryRegion<Virtual>,
+    ) -> Result<bool, &'static str> {
+        let index = self
+            .inner
+            .iter()
+            .position(|x| {
+                x.attribute_fields.mem_attributes == MemAttributes::Device
+                    && x.virt_region() == *virt_region
+            })
+            .ok_or("Region is not mapped")?;
+
+        let entry = &mut self.inner[index];
+        entry.users.pop();
+        if !entry.users.is_empty() {
+            return Ok(false);
+        }
+
+        self.inner.remove(index);
+
+        Ok(true)
+    }
+
     pub fn print(&self) {
         info!("      -------------------------------------------------------------------------------------------------------------------------------------------");
         info!(
@@ -198,6 +262,26 @@ pub fn kernel_find_and_insert_mmio_duplicate(
     })
 }
 
+/// Drop the most recently added user of an MMIO mapping.
+///
+/// Returns true if the mapping has no users left and its entry was removed.
+pub fn kernel_remove_mmio_user(virt_region: &MemoryRegion<Virtual>) -> Result<bool, &'static str> {
+    KERNEL_MAPPING_RECORD.write(|mr| mr.remove_mmio_user(virt_region))
+}
+
+/// Return the name and region of the mapping closest to `virt_addr`, if it is at most
+/// `max_distance` bytes away.
+pub fn kernel_find_nearby(
+    virt_addr: Address<Virtual>,
+    max_distance: usize,
+) -> Option<(&'static str, MemoryRegion<Virtual>)> {
+    KERNEL_MAPPING_RECORD.read(|mr| {
+        let entry = mr.find_nearby(virt_addr, max_distance)?;
+
+        Some((entry.users[0], entry.virt_region()))
+    })
+}
+
 /// Human-readable print of all recorded kernel mappings.
 pub fn kernel_print() {
     KERNEL_MAPPING_RECORD.read(|mr| mr.print());
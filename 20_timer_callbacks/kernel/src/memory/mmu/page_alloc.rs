@@ -7,7 +7,7 @@
 use super::MemoryRegion;
 use crate::{
     memory::{AddressType, Virtual},
-    synchronization::IRQSafeNullLock,
+    synchronization::Spinlock,
     warn,
 };
 use core::num::NonZeroUsize;
@@ -25,15 +25,15 @@ pub struct PageAllocator<ATYPE: AddressType> {
 // Global instances
 //--------------------------------------------------------------------------------------------------
 
-static KERNEL_MMIO_VA_ALLOCATOR: IRQSafeNullLock<PageAllocator<Virtual>> =
-    IRQSafeNullLock::new(PageAllocator::new());
+static KERNEL_MMIO_VA_ALLOCATOR: Spinlock<PageAllocator<Virtual>> =
+    Spinlock::new(PageAllocator::new());
 
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
 
 /// Return a reference to the kernel's MMIO virtual address allocator.
-pub fn kernel_mmio_va_allocator() -> &'static IRQSafeNullLock<PageAllocator<Virtual>> {
+pub fn kernel_mmio_va_allocator() -> &'static Spinlock<PageAllocator<Virtual>> {
     &KERNEL_MMIO_VA_ALLOCATOR
 }
 
@@ -67,4 +67,17 @@ impl<ATYPE: AddressType> PageAllocator<ATYPE> {
             .unwrap()
             .take_first_n_pages(num_requested_pages)
     }
+
+    /// Return pages to the allocator.
+    ///
+    /// Allocation works front to back, so pages can only be reused if they directly precede the
+    /// remaining pool. This is the case when allocations are freed in reverse order, e.g. on
+    /// rollback. Other pages are not reused.
+    pub fn free(&mut self, region: &MemoryRegion<ATYPE>) {
+        if let Some(pool) = self.pool.as_mut() {
+            if region.end_exclusive_page_addr() == pool.start_page_addr() {
+                *pool = MemoryRegion::new(region.start_page_addr(), pool.end_exclusive_page_addr());
+            }
+        }
+    }
 }
@@ -53,6 +53,18 @@ pub mod interface {
             attr: &AttributeFields,
         ) -> Result<(), &'static str>;
 
+        /// Unmap the given virtual memory region.
+        ///
+        /// Fails without changing anything if any page of the region is not mapped.
+        ///
+        /// # Safety
+        ///
+        /// - The caller must ensure that the region is not accessed anymore afterwards.
+        unsafe fn unmap_at(
+            &mut self,
+            virt_region: &MemoryRegion<Virtual>,
+        ) -> Result<(), &'static str>;
+
         /// Try to translate a virtual page address to a physical page address.
         ///
         /// Will only succeed if there exists a valid mapping for the input page.
@@ -133,5 +145,22 @@ mod tests {
         let virt_addr = virt_start_page_addr.into_inner() + 0x100;
         let phys_addr = phys_start_page_addr.into_inner() + 0x100;
         assert_eq!(tables.try_virt_addr_to_phys_addr(virt_addr), Ok(phys_addr));
+
+        // Unmapping a region that is only partially mapped must fail and change nothing.
+        let too_big = MemoryRegion::new(
+            virt_start_page_addr.checked_offset(-1).unwrap(),
+            virt_end_exclusive_page_addr,
+        );
+        unsafe { assert!(tables.unmap_at(&too_big).is_err()) };
+        assert_eq!(tables.try_page_attributes(virt_start_page_addr), Ok(attr));
+
+        unsafe { assert_eq!(tables.unmap_at(&virt_region), Ok(())) };
+        assert_eq!(
+            tables.try_page_attributes(virt_start_page_addr),
+            Err("Page marked invalid")
+        );
+
+        // The region can be mapped again.
+        unsafe { assert_eq!(tables.map_at(&virt_region, &phys_region, &attr), Ok(())) };
     }
 }
@@ -2,10 +2,21 @@
 //
 // Copyright (c) 2018-2022 Andre Richter <andre.o.richter@gmail.com>
 
-//! A panic handler that infinitely waits.
+//! A panic handler that prints the message and then halts or reboots.
+//!
+//! The message is fully transmitted before the core stops. Afterwards, the board is reset through
+//! the watchdog with the `panic_reboot` feature, and the core halts otherwise. Integration tests
+//! exit QEMU with a failure instead.
 
-use crate::{backtrace, cpu, exception, println};
-use core::panic::PanicInfo;
+use crate::{backtrace, console, cpu, exception, println};
+use core::{panic::PanicInfo, time::Duration};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// How long another core may keep holding a lock that the panic handler needs.
+const LOCK_TIMEOUT: Duration = Duration::from_millis(100);
 
 //--------------------------------------------------------------------------------------------------
 // Private Code
@@ -19,6 +30,10 @@ use core::panic::PanicInfo;
 fn _panic_exit() -> ! {
     #[cfg(not(feature = "test_build"))]
     {
+        #[cfg(feature = "panic_reboot")]
+        crate::bsp::driver::try_reboot_now();
+
+        // Also reached if the watchdog is not available yet.
         cpu::wait_forever()
     }
 
@@ -64,6 +79,19 @@ fn panic(info: &PanicInfo) -> ! {
     // Protect against panic infinite loops if any of the following code panics itself.
     panic_prevent_reenter();
 
+    // The panic might have happened while the console lock was held, e.g. in a `Display` impl of a
+    // printed value. Break the lock then. This also drains anything that is still held back and
+    // prints unbuffered from here on, so that the panic message can not get stuck in the line
+    // buffer. No memory is allocated or freed from here on, since the heap lock might be held, too.
+    unsafe { console::panic_takeover(LOCK_TIMEOUT) };
+
+    // Replay the recent history first. Some of it might not have made it out before the crash.
+    #[cfg(feature = "panic_log_replay")]
+    unsafe {
+        crate::log::panic_takeover(LOCK_TIMEOUT);
+        crate::log::dump_ring();
+    }
+
     let timestamp = crate::time::time_manager().uptime();
     let (location, line, column) = match info.location() {
         Some(loc) => (loc.file(), loc.line(), loc.column()),
@@ -84,5 +112,9 @@ fn panic(info: &PanicInfo) -> ! {
         backtrace::Backtrace
     );
 
+    // Wait until the last character is on the wire. A reboot would cut it off otherwise. This
+    // does not take the console lock, so it can not hang if another core grabbed it meanwhile.
+    console::panic_flush();
+
     _panic_exit()
 }
@@ -4,18 +4,33 @@
 
 //! Printing.
 
-use crate::console;
+use crate::{console, log};
 use core::fmt;
 
 //--------------------------------------------------------------------------------------------------
 // Public Code
 //--------------------------------------------------------------------------------------------------
 
+/// All printing macros funnel through here.
+///
+/// The message is handed to the console as a single `write_fmt()` call, which is atomic with
+/// respect to IRQs. Do not split a line into several `_print()` calls if it must not interleave.
 #[doc(hidden)]
 pub fn _print(args: fmt::Arguments) {
     console::console().write_fmt(args).unwrap();
 }
 
+/// All logging macros funnel through here.
+///
+/// Like `_print()`, but the message is also recorded in the log ring buffer.
+#[doc(hidden)]
+pub fn _print_log(args: fmt::Arguments) {
+    // Record first, so that the message is kept even if printing hangs. The ring buffer is unlocked
+    // again before the console is written, so the two locks never nest here.
+    log::_record(args);
+    _print(args);
+}
+
 /// Prints without a newline.
 ///
 /// Carbon copy from <https://doc.rust-lang.org/src/std/macros.rs.html>
@@ -35,73 +50,165 @@ macro_rules! println {
     })
 }
 
-/// Prints an info, with a newline.
+/// Prints an error, with a newline.
 #[macro_export]
-macro_rules! info {
+macro_rules! error {
     ($string:expr) => ({
-        let timestamp = $crate::time::time_manager().uptime();
+        if $crate::log::enabled($crate::log::Level::Error) {
+            let timestamp = $crate::time::time_manager().uptime();
 
-        $crate::print::_print(format_args_nl!(
-            concat!("[  {:>3}.{:06}] ", $string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-        ));
+            $crate::print::_print_log(format_args_nl!(
+                concat!("[E {:>3}.{:06}] ", $string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+            ));
+        }
     });
     ($format_string:expr, $($arg:tt)*) => ({
-        let timestamp = $crate::time::time_manager().uptime();
-
-        $crate::print::_print(format_args_nl!(
-            concat!("[  {:>3}.{:06}] ", $format_string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-            $($arg)*
-        ));
+        if $crate::log::enabled($crate::log::Level::Error) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                concat!("[E {:>3}.{:06}] ", $format_string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+                $($arg)*
+            ));
+        }
     })
 }
 
 /// Prints a warning, with a newline.
+///
+/// With a leading `color = <console::ansi::Color>,` the line is printed in that color, if ANSI
+/// escape sequences are enabled.
 #[macro_export]
 macro_rules! warn {
+    (color = $color:expr, $($arg:tt)+) => ({
+        if $crate::log::enabled($crate::log::Level::Warn) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                "{}{}{}",
+                $crate::console::ansi::Fg($color),
+                format_args!(
+                    "[W {:>3}.{:06}] {}",
+                    timestamp.as_secs(),
+                    timestamp.subsec_micros(),
+                    format_args!($($arg)+)
+                ),
+                $crate::console::ansi::Reset,
+            ));
+        }
+    });
     ($string:expr) => ({
-        let timestamp = $crate::time::time_manager().uptime();
+        if $crate::log::enabled($crate::log::Level::Warn) {
+            let timestamp = $crate::time::time_manager().uptime();
 
-        $crate::print::_print(format_args_nl!(
-            concat!("[W {:>3}.{:06}] ", $string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-        ));
+            $crate::print::_print_log(format_args_nl!(
+                concat!("[W {:>3}.{:06}] ", $string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+            ));
+        }
     });
     ($format_string:expr, $($arg:tt)*) => ({
-        let timestamp = $crate::time::time_manager().uptime();
-
-        $crate::print::_print(format_args_nl!(
-            concat!("[W {:>3}.{:06}] ", $format_string),
-            timestamp.as_secs(),
-            timestamp.subsec_micros(),
-            $($arg)*
-        ));
+        if $crate::log::enabled($crate::log::Level::Warn) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                concat!("[W {:>3}.{:06}] ", $format_string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+                $($arg)*
+            ));
+        }
     })
 }
 
+/// Prints an info, with a newline.
+///
+/// With a leading `color = <console::ansi::Color>,` the line is printed in that color, if ANSI
+/// escape sequences are enabled.
+#[macro_export]
+macro_rules! info {
+    (color = $color:expr, $($arg:tt)+) => ({
+        if $crate::log::enabled($crate::log::Level::Info) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                "{}{}{}",
+                $crate::console::ansi::Fg($color),
+                format_args!(
+                    "[  {:>3}.{:06}] {}",
+                    timestamp.as_secs(),
+                    timestamp.subsec_micros(),
+                    format_args!($($arg)+)
+                ),
+                $crate::console::ansi::Reset,
+            ));
+        }
+    });
+    ($string:expr) => ({
+        if $crate::log::enabled($crate::log::Level::Info) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                concat!("[  {:>3}.{:06}] ", $string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+            ));
+        }
+    });
+    ($format_string:expr, $($arg:tt)*) => ({
+        if $crate::log::enabled($crate::log::Level::Info) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                concat!("[  {:>3}.{:06}] ", $format_string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+                $($arg)*
+            ));
+        }
+    })
+}
+
+/// Prints an info in the given `console::ansi::Color`, with a newline.
+///
+/// Shorthand for `info!(color = $color, ...)`.
+#[macro_export]
+macro_rules! info_colored {
+    ($color:expr, $($arg:tt)+) => ($crate::info!(color = $color, $($arg)+));
+}
+
+/// Prints a warning in yellow, with a newline.
+///
+/// Shorthand for `warn!(color = Color::Yellow, ...)`.
+#[macro_export]
+macro_rules! warn_colored {
+    ($($arg:tt)+) => ($crate::warn!(color = $crate::console::ansi::Color::Yellow, $($arg)+));
+}
+
 /// Debug print, with a newline.
 #[macro_export]
 macro_rules! debug {
     ($string:expr) => ({
-        if cfg!(feature = "debug_prints") {
+        if $crate::log::enabled($crate::log::Level::Debug) {
             let timestamp = $crate::time::time_manager().uptime();
 
-            $crate::print::_print(format_args_nl!(
-                concat!("<[>D {:>3}.{:06}> ", $string),
+            $crate::print::_print_log(format_args_nl!(
+                concat!("<D {:>3}.{:06}> ", $string),
                 timestamp.as_secs(),
                 timestamp.subsec_micros(),
             ));
         }
     });
     ($format_string:expr, $($arg:tt)*) => ({
-        if cfg!(feature = "debug_prints") {
+        if $crate::log::enabled($crate::log::Level::Debug) {
             let timestamp = $crate::time::time_manager().uptime();
 
-            $crate::print::_print(format_args_nl!(
+            $crate::print::_print_log(format_args_nl!(
                 concat!("<D {:>3}.{:06}> ", $format_string),
                 timestamp.as_secs(),
                 timestamp.subsec_micros(),
@@ -110,3 +217,31 @@ macro_rules! debug {
         }
     })
 }
+
+/// Trace print, with a newline.
+#[macro_export]
+macro_rules! trace {
+    ($string:expr) => ({
+        if $crate::log::enabled($crate::log::Level::Trace) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                concat!("<T {:>3}.{:06}> ", $string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+            ));
+        }
+    });
+    ($format_string:expr, $($arg:tt)*) => ({
+        if $crate::log::enabled($crate::log::Level::Trace) {
+            let timestamp = $crate::time::time_manager().uptime();
+
+            $crate::print::_print_log(format_args_nl!(
+                concat!("<T {:>3}.{:06}> ", $format_string),
+                timestamp.as_secs(),
+                timestamp.subsec_micros(),
+                $($arg)*
+            ));
+        }
+    })
+}
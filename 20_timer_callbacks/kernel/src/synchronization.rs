@@ -10,7 +10,11 @@
 //!   - <https://stackoverflow.com/questions/59428096/understanding-the-send-trait>
 //!   - <https://doc.rust-lang.org/std/cell/index.html>
 
-use core::cell::UnsafeCell;
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
@@ -50,8 +54,9 @@ pub mod interface {
 /// In contrast to a real Mutex implementation, does not protect against concurrent access from
 /// other cores to the contained data. This part is preserved for later lessons.
 ///
-/// The lock will only be used as long as it is safe to do so, i.e. as long as the kernel is
-/// executing on a single core.
+/// The lock will only be used as long as it is safe to do so, i.e. for data that only a single core
+/// ever accesses, like the per-core instances of a `PerCore`. Data that is shared with the
+/// secondary cores must use [`Spinlock`] instead.
 pub struct IRQSafeNullLock<T>
 where
     T: ?Sized,
@@ -59,6 +64,21 @@ where
     data: UnsafeCell<T>,
 }
 
+/// A spinlock that protects against concurrent access from other cores.
+///
+/// IRQs are masked on the executing core while the lock is held. Otherwise, an IRQ handler could
+/// spin forever on a lock that the code it interrupted holds.
+///
+/// The lock relies on exclusive memory accesses, which only work once the MMU and caching are
+/// enabled.
+pub struct Spinlock<T>
+where
+    T: ?Sized,
+{
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
 /// A pseudo-lock that is RW during the single-core kernel init phase and RO afterwards.
 ///
 /// Intended to encapsulate data that is populated during kernel init when no concurrency exists.
@@ -85,6 +105,52 @@ impl<T> IRQSafeNullLock<T> {
     }
 }
 
+unsafe impl<T> Send for Spinlock<T> where T: ?Sized + Send {}
+unsafe impl<T> Sync for Spinlock<T> where T: ?Sized + Send {}
+
+impl<T> Spinlock<T> {
+    /// Create an instance.
+    pub const fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Return whether any core holds the lock right now.
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// Wait up to `timeout` for the lock to be released, and break it if it is still held then.
+    ///
+    /// Meant for the panic handler, which must get through to e.g. the console even if the panic
+    /// happened while the lock was held. Another core gets the chance to finish first.
+    ///
+    /// # Safety
+    ///
+    /// - Whoever still holds the lock after `timeout` must not touch the data anymore.
+    pub unsafe fn force_unlock(&self, timeout: Duration) {
+        let deadline = crate::time::time_manager().uptime() + timeout;
+
+        while self.is_locked() && (crate::time::time_manager().uptime() < deadline) {
+            core::hint::spin_loop();
+        }
+
+        self.locked.store(false, Ordering::Release);
+    }
+
+    /// Access the data without taking the lock.
+    ///
+    /// # Safety
+    ///
+    /// - The access must be harmless even if another core holds the lock, e.g. polling a status
+    ///   register.
+    pub unsafe fn data_unlocked(&self) -> &T {
+        &*self.data.get()
+    }
+}
+
 unsafe impl<T> Send for InitStateLock<T> where T: ?Sized + Send {}
 unsafe impl<T> Sync for InitStateLock<T> where T: ?Sized + Send {}
 
@@ -115,6 +181,32 @@ impl<T> interface::Mutex for IRQSafeNullLock<T> {
     }
 }
 
+impl<T> interface::Mutex for Spinlock<T> {
+    type Data = T;
+
+    fn lock<'a, R>(&'a self, f: impl FnOnce(&'a mut Self::Data) -> R) -> R {
+        exception::asynchronous::exec_with_irq_masked(|| {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                // Wait with plain loads, which do not claim the cache line exclusively.
+                while self.locked.load(Ordering::Relaxed) {
+                    core::hint::spin_loop();
+                }
+            }
+
+            let data = unsafe { &mut *self.data.get() };
+            let ret = f(data);
+
+            self.locked.store(false, Ordering::Release);
+
+            ret
+        })
+    }
+}
+
 impl<T> interface::ReadWriteEx for InitStateLock<T> {
     type Data = T;
 
@@ -156,4 +248,30 @@ mod tests {
 
         assert_eq!(size_of::<InitStateLock<u64>>(), size_of::<u64>());
     }
+
+    /// A Spinlock must be held exactly for the duration of the closure, and nesting different
+    /// locks must work.
+    #[kernel_test]
+    fn spinlock_is_held_during_closure() {
+        use interface::Mutex;
+
+        let outer = Spinlock::new(0_u64);
+        let inner = Spinlock::new(0_u64);
+
+        for _ in 0..1000 {
+            outer.lock(|o| {
+                assert!(outer.is_locked());
+                assert!(exception::asynchronous::is_local_irq_masked());
+
+                *o += inner.lock(|i| {
+                    *i += 1;
+                    *i
+                });
+            });
+        }
+
+        assert!(!outer.is_locked() && !inner.is_locked());
+        assert_eq!(inner.lock(|i| *i), 1000);
+        assert_eq!(outer.lock(|o| *o), 1000 * 1001 / 2);
+    }
 }
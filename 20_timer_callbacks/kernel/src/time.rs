@@ -13,31 +13,40 @@
 #[path = "_arch/aarch64/time.rs"]
 mod arch_time;
 
+mod timer_wheel;
+
 use crate::{
-    driver, exception,
+    bsp, driver, exception,
     exception::asynchronous::IRQNumber,
-    synchronization::{interface::Mutex, IRQSafeNullLock},
+    synchronization::{interface::Mutex, Spinlock},
     warn,
 };
-use alloc::{boxed::Box, vec::Vec};
+use alloc::boxed::Box;
 use core::{
-    sync::atomic::{AtomicBool, Ordering},
+    num::NonZeroU32,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
     time::Duration,
 };
+use timer_wheel::{Deadline, Node, TimerWheel};
 
 //--------------------------------------------------------------------------------------------------
 // Private Definitions
 //--------------------------------------------------------------------------------------------------
 
 struct Timeout {
+    id: u64,
     due_time: Duration,
     period: Option<Duration>,
     callback: TimeoutCallback,
 }
 
 struct OrderedTimeoutQueue {
-    // Can be replaced with a BinaryHeap once it's new() becomes const.
-    inner: Vec<Timeout>,
+    inner: TimerWheel<Timeout>,
+
+    /// The periodic timeout whose callback is executing right now. It is not part of `inner` at
+    /// that time, so a cancellation must be remembered until it would be pushed back.
+    in_flight_id: Option<u64>,
+    in_flight_cancelled: bool,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -47,9 +56,17 @@ struct OrderedTimeoutQueue {
 /// The callback type used by timer IRQs.
 pub type TimeoutCallback = Box<dyn Fn() + Send>;
 
+/// An opaque identifier for a timeout, which can be used to cancel it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimerHandle(u64);
+
 /// Provides time management functions.
+///
+/// Timeouts fire on the boot core, which is the only one that has the timer IRQ enabled. So they
+/// must be set from the boot core, too, because that programs the timer of the executing core.
 pub struct TimeManager {
-    queue: IRQSafeNullLock<OrderedTimeoutQueue>,
+    queue: Spinlock<OrderedTimeoutQueue>,
+    next_id: AtomicU64,
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -74,28 +91,47 @@ impl Timeout {
     }
 }
 
+impl Deadline for Timeout {
+    fn deadline(&self) -> Duration {
+        self.due_time
+    }
+}
+
 impl OrderedTimeoutQueue {
     pub const fn new() -> Self {
-        Self { inner: Vec::new() }
+        Self {
+            inner: TimerWheel::new(),
+            in_flight_id: None,
+            in_flight_cancelled: false,
+        }
     }
 
-    pub fn push(&mut self, timeout: Timeout) {
+    /// The timeout is passed as a node of the wheel. It can be popped and pushed again, which is
+    /// what happens on every re-arm of a periodic timeout, without allocating.
+    pub fn push(&mut self, timeout: Box<Node<Timeout>>) {
         self.inner.push(timeout);
-
-        // Note reverse compare order so that earliest expiring item is at end of vec. We do this so
-        // that we can use Vec::pop below to retrieve the item that is next due.
-        self.inner.sort_by(|a, b| b.due_time.cmp(&a.due_time));
     }
 
     pub fn peek_next_due_time(&self) -> Option<Duration> {
-        let timeout = self.inner.last()?;
+        let timeout = self.inner.peek()?;
+
+        Some(timeout.due_time)
+    }
+
+    pub fn due_time(&self, id: u64) -> Option<Duration> {
+        let timeout = self.inner.find(|timeout| timeout.id == id)?;
 
         Some(timeout.due_time)
     }
 
-    pub fn pop(&mut self) -> Option<Timeout> {
+    pub fn pop(&mut self) -> Option<Box<Node<Timeout>>> {
         self.inner.pop()
     }
+
+    /// Remove the timeout with the given id. Return true if it was found.
+    pub fn remove(&mut self, id: u64) -> bool {
+        self.inner.remove(|timeout| timeout.id == id).is_some()
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -114,7 +150,8 @@ impl TimeManager {
     /// Create an instance.
     pub const fn new() -> Self {
         Self {
-            queue: IRQSafeNullLock::new(OrderedTimeoutQueue::new()),
+            queue: Spinlock::new(OrderedTimeoutQueue::new()),
+            next_id: AtomicU64::new(0),
         }
     }
 
@@ -123,6 +160,28 @@ impl TimeManager {
         arch_time::resolution()
     }
 
+    /// The timer's counter frequency in Hz.
+    ///
+    /// This is the value read from the hardware at boot, unless overridden by `set_frequency()`.
+    pub fn frequency(&self) -> u64 {
+        arch_time::frequency()
+    }
+
+    /// Override the timer's counter frequency.
+    ///
+    /// Use this if firmware programmed a wrong value. All conversions between counter values and
+    /// durations use the new frequency from here on, so `uptime()` will jump accordingly.
+    pub fn set_frequency(&self, hz: u64) -> Result<(), &'static str> {
+        let hz = u32::try_from(hz)
+            .ok()
+            .and_then(NonZeroU32::new)
+            .ok_or("Frequency out of range")?;
+
+        arch_time::set_frequency(hz);
+
+        Ok(())
+    }
+
     /// The uptime since power-on of the device.
     ///
     /// This includes time consumed by firmware and bootloaders.
@@ -130,40 +189,105 @@ impl TimeManager {
         arch_time::uptime()
     }
 
+    /// The uptime since the first call to `time::init()`.
+    ///
+    /// In contrast to `uptime()`, this excludes the time consumed by firmware, bootloaders and
+    /// early kernel init. Before `time::init()`, it equals `uptime()`.
+    pub fn uptime_since_init(&self) -> Duration {
+        arch_time::uptime_since_init()
+    }
+
     /// Spin for a given duration.
     pub fn spin_for(&self, duration: Duration) {
         arch_time::spin_for(duration)
     }
 
+    /// Spin until `uptime()` reached the given deadline.
+    ///
+    /// In contrast to chaining `spin_for()`, this does not accumulate drift, so it can be used to
+    /// hit fixed points in time in a periodic loop. Returns immediately if the deadline has already
+    /// passed.
+    pub fn spin_until(&self, deadline: Duration) {
+        arch_time::spin_until(deadline)
+    }
+
     /// Set a timeout.
-    fn set_timeout(&self, timeout: Timeout) {
+    fn set_timeout(
+        &self,
+        delay: Duration,
+        period: Option<Duration>,
+        callback: TimeoutCallback,
+    ) -> TimerHandle {
+        let timeout = Node::new(Timeout {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            due_time: self.uptime() + delay,
+            period,
+            callback,
+        });
+        let handle = TimerHandle(timeout.id);
+
         self.queue.lock(|queue| {
             queue.push(timeout);
 
             arch_time::set_timeout_irq(queue.peek_next_due_time().unwrap());
         });
+
+        handle
     }
 
     /// Set a one-shot timeout.
-    pub fn set_timeout_once(&self, delay: Duration, callback: TimeoutCallback) {
-        let timeout = Timeout {
-            due_time: self.uptime() + delay,
-            period: None,
-            callback,
-        };
-
-        self.set_timeout(timeout);
+    pub fn set_timeout_once(&self, delay: Duration, callback: TimeoutCallback) -> TimerHandle {
+        self.set_timeout(delay, None, callback)
     }
 
     /// Set a periodic timeout.
-    pub fn set_timeout_periodic(&self, delay: Duration, callback: TimeoutCallback) {
-        let timeout = Timeout {
-            due_time: self.uptime() + delay,
-            period: Some(delay),
-            callback,
-        };
+    pub fn set_timeout_periodic(&self, delay: Duration, callback: TimeoutCallback) -> TimerHandle {
+        self.set_timeout(delay, Some(delay), callback)
+    }
 
-        self.set_timeout(timeout);
+    /// Return the time left until the earliest pending timeout fires.
+    ///
+    /// Returns `None` if no timeout is pending. A timeout that is already due yields zero.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        let due_time = self.queue.lock(|queue| queue.peek_next_due_time())?;
+
+        Some(due_time.saturating_sub(self.uptime()))
+    }
+
+    /// Return the time left until the given timeout fires.
+    ///
+    /// Returns `None` if the timeout is not pending anymore.
+    pub fn time_remaining(&self, handle: TimerHandle) -> Option<Duration> {
+        let due_time = self.queue.lock(|queue| queue.due_time(handle.0))?;
+
+        Some(due_time.saturating_sub(self.uptime()))
+    }
+
+    /// Cancel a timeout.
+    ///
+    /// Returns true if the timeout was pending and will not fire anymore. Returns false if it was
+    /// already cancelled, or if it was a one-shot timeout that already fired.
+    pub fn cancel(&self, handle: TimerHandle) -> bool {
+        self.queue.lock(|queue| {
+            // A periodic timeout might cancel itself from within its callback.
+            if queue.in_flight_id == Some(handle.0) {
+                let was_cancelled = queue.in_flight_cancelled;
+                queue.in_flight_cancelled = true;
+
+                return !was_cancelled;
+            }
+
+            if !queue.remove(handle.0) {
+                return false;
+            }
+
+            match queue.peek_next_due_time() {
+                Some(due_time) => arch_time::set_timeout_irq(due_time),
+                None => arch_time::conclude_timeout_irq(),
+            }
+
+            true
+        })
     }
 }
 
@@ -174,6 +298,19 @@ pub fn init() -> Result<(), &'static str> {
         return Err("Init already done");
     }
 
+    arch_time::mark_init();
+
+    let frequency = time_manager().frequency();
+    if (frequency != bsp::time::EXPECTED_TIMER_FREQUENCY)
+        && (frequency != bsp::time::QEMU_TIMER_FREQUENCY)
+    {
+        warn!(
+            "Timer frequency is {} Hz, but {} Hz was expected. Use set_frequency() to override",
+            frequency,
+            bsp::time::EXPECTED_TIMER_FREQUENCY
+        );
+    }
+
     let timer_descriptor =
         driver::DeviceDriverDescriptor::new(time_manager(), None, Some(arch_time::timeout_irq()));
     driver::driver_manager().register_driver(timer_descriptor);
@@ -212,7 +349,7 @@ impl exception::asynchronous::interface::IRQHandler for TimeManager {
     fn handle(&self) -> Result<(), &'static str> {
         arch_time::conclude_timeout_irq();
 
-        let maybe_timeout: Option<Timeout> = self.queue.lock(|queue| {
+        let maybe_timeout: Option<Box<Node<Timeout>>> = self.queue.lock(|queue| {
             let next_due_time = queue.peek_next_due_time()?;
             if next_due_time > self.uptime() {
                 return None;
@@ -223,6 +360,9 @@ impl exception::asynchronous::interface::IRQHandler for TimeManager {
             // Refresh as early as possible to prevent drift.
             if timeout.is_periodic() {
                 timeout.refresh();
+
+                queue.in_flight_id = Some(timeout.id);
+                queue.in_flight_cancelled = false;
             }
 
             Some(timeout)
@@ -242,14 +382,16 @@ impl exception::asynchronous::interface::IRQHandler for TimeManager {
         (timeout.callback)();
 
         self.queue.lock(|queue| {
-            if timeout.is_periodic() {
-                // There might be some overhead involved in the periodic path, because the timeout
-                // item is first popped from the underlying Vec and then pushed back again. It could
-                // be faster to keep the item in the queue and find a way to work with a reference
-                // to it.
+            let cancelled = queue.in_flight_cancelled;
+            queue.in_flight_id = None;
+            queue.in_flight_cancelled = false;
+
+            if timeout.is_periodic() && !cancelled {
+                // The timeout item is popped from the wheel and then pushed back again. This keeps
+                // the code simple and the focus on the high-level concepts.
                 //
-                // We are not going this route on purpose, though. It allows to keep the code simple
-                // and the focus on the high-level concepts.
+                // Re-arming does not allocate: The popped node is moved back into the wheel as a
+                // whole, including the boxed callback.
                 queue.push(timeout);
             };
 
@@ -261,3 +403,74 @@ impl exception::asynchronous::interface::IRQHandler for TimeManager {
         Ok(())
     }
 }
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::heap_alloc::kernel_heap_allocator;
+    use core::sync::atomic::AtomicUsize;
+    use test_macros::kernel_test;
+
+    /// Out of range frequencies must be rejected, valid ones must be used.
+    #[kernel_test]
+    fn set_frequency_works() {
+        let frequency = time_manager().frequency();
+
+        let too_big = u64::from(u32::MAX) + 1;
+
+        assert!(time_manager().set_frequency(0).is_err());
+        assert!(time_manager().set_frequency(too_big).is_err());
+        assert_eq!(time_manager().frequency(), frequency);
+
+        // Overriding with the current value must not change anything.
+        assert!(time_manager().set_frequency(frequency).is_ok());
+        assert_eq!(time_manager().frequency(), frequency);
+    }
+
+    /// Re-arming a periodic timeout must not allocate.
+    ///
+    /// Mirrors the periodic path of the timer IRQ handler, without depending on timer IRQs.
+    #[kernel_test]
+    fn periodic_rearm_does_not_allocate() {
+        static NUM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        let mut queue = OrderedTimeoutQueue::new();
+
+        // Entries on a high level of the wheel, which the periodic timeout must pass by.
+        for i in 0..32 {
+            queue.push(Node::new(Timeout {
+                id: i,
+                due_time: Duration::from_secs(1000 + i),
+                period: None,
+                callback: Box::new(|| {}),
+            }));
+        }
+
+        queue.push(Node::new(Timeout {
+            id: 32,
+            due_time: Duration::from_millis(1),
+            period: Some(Duration::from_millis(1)),
+            callback: Box::new(|| {
+                NUM_CALLS.fetch_add(1, Ordering::Relaxed);
+            }),
+        }));
+
+        let used_before = kernel_heap_allocator().used();
+
+        for _ in 0..1000 {
+            let mut timeout = queue.pop().unwrap();
+            assert!(timeout.is_periodic());
+
+            timeout.refresh();
+            (timeout.callback)();
+            queue.push(timeout);
+        }
+
+        assert_eq!(NUM_CALLS.load(Ordering::Relaxed), 1000);
+        assert_eq!(kernel_heap_allocator().used(), used_before);
+    }
+}
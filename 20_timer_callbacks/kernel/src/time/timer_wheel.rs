@@ -0,0 +1,416 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! A hierarchical timer wheel.
+//!
+//! Items are sorted into slots by their deadline in ticks of one millisecond. Level 0 has one slot
+//! per tick, and each higher level has slots that are `SLOTS` times wider. An item is kept on the
+//! lowest level on which its deadline shares the parent slot with the current tick. This has two
+//! consequences:
+//!
+//! - All items on a lower level are due before all items on a higher level.
+//! - On each level, items in lower slots are due before items in higher slots.
+//!
+//! Each slot is kept sorted by deadline on insert, so finding the earliest item is a scan over the
+//! slot heads. When the current tick advances into a new parent slot, the items of that slot are
+//! cascaded down to lower levels. Deadlines too far in the future are parked in an overflow list.
+//!
+//! Slots are intrusive linked lists of boxed nodes. Once a node is allocated, moving it between
+//! slots, or popping and pushing it again, does not allocate.
+
+use alloc::boxed::Box;
+use core::{
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+
+//--------------------------------------------------------------------------------------------------
+// Private Definitions
+//--------------------------------------------------------------------------------------------------
+
+const SLOT_BITS: usize = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+const LEVELS: usize = 4;
+
+type Link<T> = Option<Box<Node<T>>>;
+
+/// Slot position of an item, with `level == LEVELS` standing for the overflow list.
+#[derive(Copy, Clone)]
+struct Position {
+    level: usize,
+    slot: usize,
+    index: usize,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// Items that can be stored in a `TimerWheel`.
+pub trait Deadline {
+    /// The point in time at which the item is due.
+    fn deadline(&self) -> Duration;
+}
+
+/// A node of the wheel, owning one item.
+pub struct Node<T> {
+    item: T,
+    next: Link<T>,
+}
+
+/// A hierarchical timer wheel.
+pub struct TimerWheel<T> {
+    levels: [[Link<T>; SLOTS]; LEVELS],
+    overflow: Link<T>,
+    now_tick: u64,
+}
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+fn tick(deadline: Duration) -> u64 {
+    deadline.as_millis() as u64
+}
+
+fn slot_index(tick: u64, level: usize) -> usize {
+    ((tick >> (SLOT_BITS * level)) as usize) & (SLOTS - 1)
+}
+
+/// Return true if both ticks fall into the same slot of the level above `level`.
+fn same_parent(a: u64, b: u64, level: usize) -> bool {
+    (a >> (SLOT_BITS * (level + 1))) == (b >> (SLOT_BITS * (level + 1)))
+}
+
+/// Insert a node behind all nodes that are not due later, which keeps the list sorted.
+fn insert_sorted<T: Deadline>(link: &mut Link<T>, mut node: Box<Node<T>>) {
+    let deadline = node.item.deadline();
+
+    let mut cur = link;
+    while cur
+        .as_ref()
+        .map_or(false, |next| next.item.deadline() <= deadline)
+    {
+        cur = &mut cur.as_mut().unwrap().next;
+    }
+
+    node.next = cur.take();
+    *cur = Some(node);
+}
+
+/// Return the index of the first node whose item matches `pred`.
+fn index_of<T>(link: &Link<T>, pred: &impl Fn(&T) -> bool) -> Option<usize> {
+    let mut cur = link.as_deref();
+    let mut i = 0;
+
+    while let Some(node) = cur {
+        if pred(&node.item) {
+            return Some(i);
+        }
+
+        cur = node.next.as_deref();
+        i += 1;
+    }
+
+    None
+}
+
+fn nth<T>(link: &Link<T>, n: usize) -> &Node<T> {
+    let mut node = link.as_deref().unwrap();
+    for _ in 0..n {
+        node = node.next.as_deref().unwrap();
+    }
+
+    node
+}
+
+fn unlink_nth<T>(link: &mut Link<T>, n: usize) -> Box<Node<T>> {
+    let mut cur = link;
+    for _ in 0..n {
+        cur = &mut cur.as_mut().unwrap().next;
+    }
+
+    let mut node = cur.take().unwrap();
+    *cur = node.next.take();
+
+    node
+}
+
+impl<T: Deadline> TimerWheel<T> {
+    const EMPTY_SLOT: Link<T> = None;
+    const EMPTY_LEVEL: [Link<T>; SLOTS] = [Self::EMPTY_SLOT; SLOTS];
+
+    fn list(&self, level: usize, slot: usize) -> &Link<T> {
+        if level == LEVELS {
+            &self.overflow
+        } else {
+            &self.levels[level][slot]
+        }
+    }
+
+    fn list_mut(&mut self, level: usize, slot: usize) -> &mut Link<T> {
+        if level == LEVELS {
+            &mut self.overflow
+        } else {
+            &mut self.levels[level][slot]
+        }
+    }
+
+    /// The earliest node is the head of the first non-empty slot, because slots are sorted.
+    fn earliest(&self) -> Option<Position> {
+        for level in 0..LEVELS {
+            for slot in slot_index(self.now_tick, level)..SLOTS {
+                if self.levels[level][slot].is_some() {
+                    return Some(Position {
+                        level,
+                        slot,
+                        index: 0,
+                    });
+                }
+            }
+        }
+
+        self.overflow.as_ref()?;
+
+        Some(Position {
+            level: LEVELS,
+            slot: 0,
+            index: 0,
+        })
+    }
+
+    fn find_position(&self, pred: &impl Fn(&T) -> bool) -> Option<Position> {
+        for level in 0..=LEVELS {
+            let num_slots = if level == LEVELS { 1 } else { SLOTS };
+
+            for slot in 0..num_slots {
+                if let Some(index) = index_of(self.list(level, slot), pred) {
+                    return Some(Position { level, slot, index });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Re-insert all nodes of a list.
+    fn cascade(&mut self, level: usize, slot: usize) {
+        let mut list = self.list_mut(level, slot).take();
+
+        while let Some(mut node) = list {
+            list = node.next.take();
+            self.push(node);
+        }
+    }
+
+    /// Move the current tick forward and restore the level invariants.
+    fn advance_to(&mut self, new_tick: u64) {
+        if new_tick <= self.now_tick {
+            return;
+        }
+
+        let old_tick = self.now_tick;
+        self.now_tick = new_tick;
+
+        // Top-down, so that cascaded nodes are already placed when the lower levels are checked.
+        if !same_parent(old_tick, new_tick, LEVELS - 1) {
+            self.cascade(LEVELS, 0);
+        }
+
+        for level in (1..LEVELS).rev() {
+            if !same_parent(old_tick, new_tick, level - 1) {
+                self.cascade(level, slot_index(new_tick, level));
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+impl<T> Node<T> {
+    /// Create a node that can be pushed into a `TimerWheel`.
+    pub fn new(item: T) -> Box<Self> {
+        Box::new(Self { item, next: None })
+    }
+}
+
+impl<T> Deref for Node<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.item
+    }
+}
+
+impl<T> DerefMut for Node<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.item
+    }
+}
+
+impl<T: Deadline> TimerWheel<T> {
+    /// Create an instance.
+    pub const fn new() -> Self {
+        Self {
+            levels: [Self::EMPTY_LEVEL; LEVELS],
+            overflow: None,
+            now_tick: 0,
+        }
+    }
+
+    /// Insert a node.
+    ///
+    /// Deadlines before the earliest popped one are treated as due right away.
+    pub fn push(&mut self, node: Box<Node<T>>) {
+        let due_tick = tick(node.item.deadline()).max(self.now_tick);
+
+        let list = match (0..LEVELS).find(|&level| same_parent(due_tick, self.now_tick, level)) {
+            None => &mut self.overflow,
+            Some(level) => &mut self.levels[level][slot_index(due_tick, level)],
+        };
+
+        insert_sorted(list, node);
+    }
+
+    /// Return the item with the earliest deadline.
+    pub fn peek(&self) -> Option<&T> {
+        let pos = self.earliest()?;
+
+        Some(&nth(self.list(pos.level, pos.slot), pos.index).item)
+    }
+
+    /// Remove and return the node with the earliest deadline.
+    pub fn pop(&mut self) -> Option<Box<Node<T>>> {
+        let pos = self.earliest()?;
+        let node = unlink_nth(self.list_mut(pos.level, pos.slot), pos.index);
+
+        self.advance_to(tick(node.item.deadline()));
+
+        Some(node)
+    }
+
+    /// Return the first item that matches `pred`.
+    pub fn find(&self, pred: impl Fn(&T) -> bool) -> Option<&T> {
+        let pos = self.find_position(&pred)?;
+
+        Some(&nth(self.list(pos.level, pos.slot), pos.index).item)
+    }
+
+    /// Remove and return the first node whose item matches `pred`.
+    pub fn remove(&mut self, pred: impl Fn(&T) -> bool) -> Option<Box<Node<T>>> {
+        let pos = self.find_position(&pred)?;
+
+        Some(unlink_nth(self.list_mut(pos.level, pos.slot), pos.index))
+    }
+}
+
+impl<T> Drop for TimerWheel<T> {
+    fn drop(&mut self) {
+        // Unlink iteratively. The default drop would recurse once per node.
+        let lists = self.levels.iter_mut().flatten().chain([&mut self.overflow]);
+
+        for link in lists {
+            let mut list = link.take();
+            while let Some(mut node) = list {
+                list = node.next.take();
+            }
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    struct Item(Duration);
+
+    impl Deadline for Item {
+        fn deadline(&self) -> Duration {
+            self.0
+        }
+    }
+
+    /// A simple linear congruential generator for reproducible pseudo-random deadlines.
+    fn next_random(state: &mut u64) -> u64 {
+        *state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+
+        *state >> 33
+    }
+
+    /// Items must come out in deadline order, across all levels and the overflow list.
+    #[kernel_test]
+    fn timer_wheel_pops_in_order() {
+        let mut wheel: TimerWheel<Item> = TimerWheel::new();
+        let mut state = 42;
+
+        for _ in 0..500 {
+            // Up to roughly 8 hours, which exceeds the range of the highest level.
+            let nanos = next_random(&mut state) % (8 * 3600 * 1_000_000_000);
+            wheel.push(Node::new(Item(Duration::from_nanos(nanos))));
+        }
+
+        let mut last = Duration::ZERO;
+        for _ in 0..500 {
+            let node = wheel.pop().unwrap();
+            assert!(node.0 >= last);
+
+            last = node.0;
+        }
+
+        assert!(wheel.pop().is_none());
+    }
+
+    /// Pushing while popping must keep the order, which is the periodic timeout pattern.
+    #[kernel_test]
+    fn timer_wheel_interleaved_push_pop() {
+        let mut wheel: TimerWheel<Item> = TimerWheel::new();
+        let mut state = 7;
+
+        for i in 0..100 {
+            wheel.push(Node::new(Item(Duration::from_millis(i * 37))));
+        }
+
+        let mut last = Duration::ZERO;
+        for _ in 0..1000 {
+            let mut node = wheel.pop().unwrap();
+            assert!(node.0 >= last);
+            if let Some(next) = wheel.peek() {
+                assert!(next.0 >= node.0);
+            }
+
+            last = node.0;
+            node.0 += Duration::from_millis(1 + next_random(&mut state) % 5000);
+            wheel.push(node);
+        }
+    }
+
+    /// Items can be found and removed by predicate.
+    #[kernel_test]
+    fn timer_wheel_find_and_remove() {
+        let mut wheel: TimerWheel<Item> = TimerWheel::new();
+
+        for secs in [1, 100, 10_000, 100_000] {
+            wheel.push(Node::new(Item(Duration::from_secs(secs))));
+        }
+
+        let is_100 = |item: &Item| item.0 == Duration::from_secs(100);
+        assert!(wheel.find(is_100).is_some());
+        assert!(wheel.remove(is_100).is_some());
+        assert!(wheel.find(is_100).is_none());
+        assert!(wheel.remove(is_100).is_none());
+
+        assert_eq!(wheel.pop().unwrap().0, Duration::from_secs(1));
+        assert_eq!(wheel.pop().unwrap().0, Duration::from_secs(10_000));
+        assert_eq!(wheel.pop().unwrap().0, Duration::from_secs(100_000));
+    }
+}
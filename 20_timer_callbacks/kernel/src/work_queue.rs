@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Deferred work, aka bottom halves.
+//!
+//! IRQ handlers block all other IRQs while they run, so they should return quickly. Work that does
+//! not need to happen right away can be handed to `schedule_work()`. It is executed later by
+//! `run_pending_work()`, outside of interrupt context.
+//!
+//! Each core has its own queue. Work runs on the core that scheduled it.
+
+use crate::{
+    bsp,
+    cpu::per_core::PerCore,
+    synchronization::{interface::Mutex, IRQSafeNullLock},
+};
+use alloc::{boxed::Box, vec::Vec};
+
+//--------------------------------------------------------------------------------------------------
+// Public Definitions
+//--------------------------------------------------------------------------------------------------
+
+/// The type of deferred work items.
+pub type Work = Box<dyn FnOnce() + Send>;
+
+//--------------------------------------------------------------------------------------------------
+// Global instances
+//--------------------------------------------------------------------------------------------------
+
+// Array repeat expressions need a constant for types that are not `Copy`.
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_QUEUE: IRQSafeNullLock<Vec<Work>> = IRQSafeNullLock::new(Vec::new());
+
+static WORK_QUEUE: PerCore<IRQSafeNullLock<Vec<Work>>> =
+    PerCore::new([EMPTY_QUEUE; bsp::cpu::NUM_CORES]);
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Queue work for later execution on the executing core.
+///
+/// Can be called from interrupt context.
+pub fn schedule_work(work: Work) {
+    WORK_QUEUE.get().lock(|queue| queue.push(work));
+}
+
+/// Return whether work is queued on the executing core.
+pub fn has_pending_work() -> bool {
+    WORK_QUEUE.get().lock(|queue| !queue.is_empty())
+}
+
+/// Execute all work queued on the executing core, in the order it was scheduled.
+///
+/// Work that is scheduled while this function runs is executed before it returns, too. Must not
+/// be called from interrupt context.
+pub fn run_pending_work() {
+    loop {
+        // Take the whole queue, so that the work items run without the lock held and IRQs can be
+        // serviced in between.
+        let pending = WORK_QUEUE.get().lock(core::mem::take);
+        if pending.is_empty() {
+            return;
+        }
+
+        for work in pending {
+            work();
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exception;
+    use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use test_macros::kernel_test;
+
+    /// Work scheduled from an IRQ handler must run exactly once, and only when drained.
+    #[kernel_test]
+    fn scheduled_work_runs_once() {
+        static IN_IRQ: AtomicBool = AtomicBool::new(false);
+        static NUM_RUNS: AtomicUsize = AtomicUsize::new(0);
+        static RAN_IN_IRQ: AtomicBool = AtomicBool::new(false);
+
+        // Simulate an IRQ handler, which runs with IRQs masked.
+        exception::asynchronous::exec_with_irq_masked(|| {
+            IN_IRQ.store(true, Ordering::Relaxed);
+
+            schedule_work(Box::new(|| {
+                if IN_IRQ.load(Ordering::Relaxed) {
+                    RAN_IN_IRQ.store(true, Ordering::Relaxed);
+                }
+
+                NUM_RUNS.fetch_add(1, Ordering::Relaxed);
+            }));
+
+            IN_IRQ.store(false, Ordering::Relaxed);
+        });
+
+        assert_eq!(NUM_RUNS.load(Ordering::Relaxed), 0);
+
+        run_pending_work();
+        assert_eq!(NUM_RUNS.load(Ordering::Relaxed), 1);
+
+        run_pending_work();
+        assert_eq!(NUM_RUNS.load(Ordering::Relaxed), 1);
+
+        assert!(!RAN_IN_IRQ.load(Ordering::Relaxed));
+    }
+}
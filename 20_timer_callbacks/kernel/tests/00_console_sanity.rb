@@ -40,9 +40,20 @@ class RxStatisticsTest < SubtestBase
     end
 end
 
+# Check that a read with timeout gives up. Depends on test 3 being run first.
+class RxTimeoutTest < SubtestBase
+    def name
+        'Receive with timeout'
+    end
+
+    def run(qemu_out, _qemu_in)
+        expect_or_raise(qemu_out, 'Timeout')
+    end
+end
+
 ##--------------------------------------------------------------------------------------------------
 ## Test registration
 ##--------------------------------------------------------------------------------------------------
 def subtest_collection
-    [TxRxHandshakeTest.new, TxStatisticsTest.new, RxStatisticsTest.new]
+    [TxRxHandshakeTest.new, TxStatisticsTest.new, RxStatisticsTest.new, RxTimeoutTest.new]
 end
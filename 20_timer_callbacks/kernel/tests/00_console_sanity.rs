@@ -11,7 +11,8 @@
 /// Console tests should time out on the I/O harness in case of panic.
 mod panic_wait_forever;
 
-use libkernel::{bsp, console, cpu, exception, memory, print};
+use core::time::Duration;
+use libkernel::{bsp, console, cpu, exception, memory, print, time};
 
 #[no_mangle]
 unsafe fn kernel_init() -> ! {
@@ -33,6 +34,13 @@ unsafe fn kernel_init() -> ! {
     // 3
     print!("{}", console().chars_read());
 
+    // No more input is sent, so the read must give up after the timeout.
+    let timeout = Duration::from_millis(100);
+    let t1 = time::time_manager().uptime();
+    assert_eq!(console::read_char_timeout(timeout), None);
+    assert!(time::time_manager().uptime() - t1 >= timeout);
+    print!("Timeout");
+
     // The QEMU process running this test will be closed by the I/O test harness.
     cpu::wait_forever();
 }
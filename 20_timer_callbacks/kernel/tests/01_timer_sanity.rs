@@ -22,6 +22,9 @@ unsafe fn kernel_init() -> ! {
 
     // Depending on CPU arch, some timer bring-up code could go here. Not needed for the RPi.
 
+    // Start the clock of uptime_since_init().
+    time::init().unwrap();
+
     test_main();
 
     cpu::qemu_exit_success()
@@ -49,3 +52,50 @@ fn spin_accuracy_check_1_second() {
 
     assert_eq!((t2 - t1).as_secs(), 1)
 }
+
+/// Uptime must increase monotonically across spins.
+#[kernel_test]
+fn uptime_increases_across_spins() {
+    let t1 = time::time_manager().uptime();
+    time::time_manager().spin_for(Duration::from_millis(10));
+    let t2 = time::time_manager().uptime();
+    time::time_manager().spin_for(Duration::from_millis(10));
+    let t3 = time::time_manager().uptime();
+
+    assert!(t2 - t1 >= Duration::from_millis(10));
+    assert!(t3 - t2 >= Duration::from_millis(10));
+}
+
+/// The uptime since init must start at `time::init()` and advance like the uptime.
+#[kernel_test]
+fn uptime_since_init_starts_at_init() {
+    let t1 = time::time_manager().uptime_since_init();
+    assert!(t1 < time::time_manager().uptime());
+
+    time::time_manager().spin_for(Duration::from_millis(10));
+    let t2 = time::time_manager().uptime_since_init();
+
+    assert!(t2 - t1 >= Duration::from_millis(10));
+}
+
+/// Spinning to absolute deadlines must not accumulate drift.
+#[kernel_test]
+fn spin_until_hits_deadlines() {
+    let period = Duration::from_millis(10);
+    let start = time::time_manager().uptime();
+
+    for i in 1..=10 {
+        time::time_manager().spin_until(start + period * i);
+        assert!(time::time_manager().uptime() >= start + period * i);
+    }
+
+    let elapsed = time::time_manager().uptime() - start;
+    assert!(elapsed < period * 11);
+
+    // A deadline in the past must return immediately.
+    let t1 = time::time_manager().uptime();
+    time::time_manager().spin_until(start);
+    let t2 = time::time_manager().uptime();
+
+    assert!(t2 - t1 < Duration::from_millis(1));
+}
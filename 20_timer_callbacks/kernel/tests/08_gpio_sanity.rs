@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! GPIO sanity tests.
+
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(libkernel::test_runner)]
+
+use libkernel::{bsp, cpu, exception, memory};
+use test_macros::kernel_test;
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    if let Err(x) = bsp::driver::init() {
+        panic!("Error initializing BSP driver subsystem: {}", x);
+    }
+
+    test_main();
+
+    cpu::qemu_exit_success()
+}
+
+/// Drive a pin of the second bank and read back its level.
+#[kernel_test]
+fn bank_1_pin_can_be_toggled() {
+    let gpio = unsafe { bsp::driver::gpio() };
+
+    gpio.set_output_pin(47);
+
+    gpio.set_pin_on(47);
+    assert!(gpio.read_pin(47));
+
+    gpio.set_pin_off(47);
+    assert!(!gpio.read_pin(47));
+}
+
+/// The level of an output pin of the first bank can be read back.
+#[kernel_test]
+fn bank_0_pin_level_can_be_read() {
+    let gpio = unsafe { bsp::driver::gpio() };
+
+    gpio.set_output_pin(21);
+
+    gpio.set_pin_on(21);
+    assert!(gpio.read_pin(21));
+
+    gpio.set_pin_off(21);
+    assert!(!gpio.read_pin(21));
+
+    gpio.map_pin_input(21);
+}
+
+/// Several pins change with one call.
+#[kernel_test]
+fn pins_can_be_set_and_cleared_in_batch() {
+    let gpio = unsafe { bsp::driver::gpio() };
+    let mask = (1 << 20) | (1 << 21);
+
+    gpio.set_output_pin(20);
+    gpio.set_output_pin(21);
+
+    gpio.set_pins(mask);
+    assert!(gpio.read_pin(20) && gpio.read_pin(21));
+
+    gpio.clear_pins(mask);
+    assert!(!gpio.read_pin(20) && !gpio.read_pin(21));
+
+    gpio.map_pin_input(20);
+    gpio.map_pin_input(21);
+}
+
+/// A typestate output pin drives the HW.
+#[kernel_test]
+fn output_pin_drives_level() {
+    let gpio = unsafe { bsp::driver::gpio() };
+    let pin = gpio.into_output(21);
+
+    pin.set_high();
+    assert!(gpio.read_pin(21));
+
+    pin.toggle();
+    assert!(!gpio.read_pin(21));
+
+    let pin = pin.into_input();
+    assert_eq!(pin.pin(), 21);
+}
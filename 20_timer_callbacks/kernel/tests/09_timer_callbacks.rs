@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Timer callback tests.
+
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(libkernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+use libkernel::{bsp, cpu, driver, exception, memory, time};
+use test_macros::kernel_test;
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    if let Err(x) = time::init() {
+        panic!("Error initializing timer subsystem: {}", x);
+    }
+
+    if let Err(x) = bsp::driver::init() {
+        panic!("Error initializing BSP driver subsystem: {}", x);
+    }
+
+    driver::driver_manager().init_drivers_and_irqs();
+    exception::asynchronous::local_irq_unmask();
+
+    test_main();
+
+    cpu::qemu_exit_success()
+}
+
+/// Spin until `counter` reached `target`, but at most for one second.
+fn wait_for(counter: &AtomicUsize, target: usize) {
+    let deadline = time::time_manager().uptime() + Duration::from_secs(1);
+
+    while counter.load(Ordering::Relaxed) < target {
+        assert!(
+            time::time_manager().uptime() < deadline,
+            "Timeout did not fire"
+        );
+    }
+}
+
+/// A cancelled periodic timeout must stop firing.
+#[kernel_test]
+fn periodic_timeout_can_be_cancelled() {
+    static NUM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let handle = time::time_manager().set_timeout_periodic(
+        Duration::from_millis(10),
+        Box::new(|| {
+            NUM_CALLS.fetch_add(1, Ordering::Relaxed);
+        }),
+    );
+
+    wait_for(&NUM_CALLS, 2);
+    assert!(time::time_manager().cancel(handle));
+
+    let num_calls = NUM_CALLS.load(Ordering::Relaxed);
+    time::time_manager().spin_for(Duration::from_millis(50));
+    assert_eq!(NUM_CALLS.load(Ordering::Relaxed), num_calls);
+
+    assert!(!time::time_manager().cancel(handle));
+}
+
+/// Timeouts must keep firing while the core sleeps in `wfi`.
+#[kernel_test]
+fn timeout_wakes_up_idle_core() {
+    use bsp::exception::asynchronous::irq_map;
+    use exception::asynchronous::{interface::IRQManager, irq_manager};
+
+    static FIRED: AtomicBool = AtomicBool::new(false);
+
+    let irqs_before = irq_manager().count(&irq_map::ARM_NS_PHYSICAL_TIMER);
+    time::time_manager().set_timeout_once(
+        Duration::from_millis(10),
+        Box::new(|| FIRED.store(true, Ordering::Relaxed)),
+    );
+
+    while !FIRED.load(Ordering::Relaxed) {
+        cpu::wait_for_interrupt();
+    }
+
+    assert!(irq_manager().count(&irq_map::ARM_NS_PHYSICAL_TIMER) > irqs_before);
+}
+
+/// Remaining time must be reported for pending timeouts only.
+#[kernel_test]
+fn time_remaining_is_reported() {
+    let delay = Duration::from_secs(10);
+    let handle = time::time_manager().set_timeout_once(delay, Box::new(|| {}));
+
+    let remaining = time::time_manager().time_remaining(handle).unwrap();
+    assert!(remaining > Duration::ZERO && remaining <= delay);
+
+    let next = time::time_manager().next_deadline().unwrap();
+    assert!(next <= remaining);
+
+    assert!(time::time_manager().cancel(handle));
+    assert_eq!(time::time_manager().time_remaining(handle), None);
+}
+
+/// Cancelling a one-shot timeout that already fired is a no-op.
+#[kernel_test]
+fn fired_one_shot_cancel_is_noop() {
+    static NUM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let handle = time::time_manager().set_timeout_once(
+        Duration::from_millis(1),
+        Box::new(|| {
+            NUM_CALLS.fetch_add(1, Ordering::Relaxed);
+        }),
+    );
+
+    wait_for(&NUM_CALLS, 1);
+    assert!(!time::time_manager().cancel(handle));
+}
+
+/// Many timeouts must all fire, in the order of their due times.
+#[kernel_test]
+fn many_timeouts_fire_in_order() {
+    const NUM_TIMEOUTS: usize = 1000;
+
+    static NUM_CALLS: AtomicUsize = AtomicUsize::new(0);
+    static OUT_OF_ORDER: AtomicBool = AtomicBool::new(false);
+
+    // All delays are relative to a common base, a few milliseconds apart. The base leaves enough
+    // room so that registration is done before the first timeout fires. The spacing is wide enough
+    // that the time passing during registration can't swap two neighbors.
+    const BASE_DELAY: Duration = Duration::from_millis(500);
+    const SPACING: Duration = Duration::from_millis(3);
+
+    for i in 0..NUM_TIMEOUTS {
+        // 7919 is prime, so this registers the due times in a scrambled order.
+        let rank = (i * 7919) % NUM_TIMEOUTS;
+
+        time::time_manager().set_timeout_once(
+            BASE_DELAY + SPACING * rank as u32,
+            Box::new(move || {
+                if NUM_CALLS.fetch_add(1, Ordering::Relaxed) != rank {
+                    OUT_OF_ORDER.store(true, Ordering::Relaxed);
+                }
+            }),
+        );
+    }
+
+    time::time_manager().spin_for(BASE_DELAY + SPACING * NUM_TIMEOUTS as u32);
+    wait_for(&NUM_CALLS, NUM_TIMEOUTS);
+
+    assert!(!OUT_OF_ORDER.load(Ordering::Relaxed));
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Tests for IRQ handler registration and bookkeeping.
+
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(libkernel::test_runner)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use libkernel::{bsp, cpu, driver, exception, memory, state, time};
+use test_macros::kernel_test;
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    if let Err(x) = time::init() {
+        panic!("Error initializing timer subsystem: {}", x);
+    }
+
+    if let Err(x) = bsp::driver::init() {
+        panic!("Error initializing BSP driver subsystem: {}", x);
+    }
+
+    driver::driver_manager().init_drivers_and_irqs();
+    exception::asynchronous::local_irq_unmask();
+
+    // Registration must work after kernel init, too.
+    state::state_manager().transition_to_single_core_main();
+
+    test_main();
+
+    cpu::qemu_exit_success()
+}
+
+/// A closure can be registered once per IRQ.
+#[kernel_test]
+fn closure_handler_registration_works() {
+    use bsp::exception::asynchronous::irq_map;
+    use exception::asynchronous::{interface::IRQManager, irq_manager};
+
+    assert!(irq_manager()
+        .register_handler_fn(irq_map::GPIO, Box::new(|| {}))
+        .is_ok());
+    irq_manager().enable(&irq_map::GPIO);
+
+    // Duplicates must be rejected, no matter how the first handler was registered.
+    assert!(irq_manager()
+        .register_handler_fn(irq_map::GPIO, Box::new(|| {}))
+        .is_err());
+    assert!(irq_manager()
+        .register_handler_fn(irq_map::ARM_NS_PHYSICAL_TIMER, Box::new(|| {}))
+        .is_err());
+}
+
+/// Dispatches must be counted per IRQ.
+#[kernel_test]
+fn irq_dispatches_are_counted() {
+    use bsp::exception::asynchronous::irq_map;
+    use exception::asynchronous::{interface::IRQManager, irq_manager};
+
+    static FIRED: AtomicBool = AtomicBool::new(false);
+
+    let before = irq_manager().count(&irq_map::ARM_NS_PHYSICAL_TIMER);
+
+    time::time_manager().set_timeout_once(
+        Duration::from_millis(1),
+        Box::new(|| FIRED.store(true, Ordering::Relaxed)),
+    );
+    time::time_manager().spin_for(Duration::from_millis(100));
+
+    assert!(FIRED.load(Ordering::Relaxed));
+    assert!(irq_manager().count(&irq_map::ARM_NS_PHYSICAL_TIMER) > before);
+}
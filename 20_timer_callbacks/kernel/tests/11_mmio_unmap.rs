@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! MMIO unmapping tests.
+
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(libkernel::test_runner)]
+
+use libkernel::{
+    bsp, cpu, exception, memory,
+    memory::{
+        mmu::{self, MMIODescriptor, PageAddress},
+        Address, Virtual,
+    },
+    state,
+};
+use test_macros::kernel_test;
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    test_main();
+
+    cpu::qemu_exit_success()
+}
+
+/// A region that is not used by any driver. It is only mapped, never accessed.
+const TEST_REGION: MMIODescriptor = MMIODescriptor::new(Address::new(0x1000_0000), 0x100);
+
+fn is_mapped(virt_addr: Address<Virtual>) -> bool {
+    mmu::try_kernel_virt_page_addr_to_phys_page_addr(PageAddress::from(virt_addr.align_down_page()))
+        .is_ok()
+}
+
+/// An unmapped region must be gone, and unmapping it again must fail.
+#[kernel_test]
+fn unmap_removes_mapping() {
+    unsafe {
+        let virt_addr = mmu::kernel_map_mmio("Test", &TEST_REGION).unwrap();
+        assert!(is_mapped(virt_addr));
+
+        assert_eq!(mmu::kernel_unmap_mmio(virt_addr, 0x100), Ok(()));
+        assert!(!is_mapped(virt_addr));
+
+        assert!(mmu::kernel_unmap_mmio(virt_addr, 0x100).is_err());
+
+        // The freed virtual pages are handed out again.
+        let new_virt_addr = mmu::kernel_map_mmio("Test", &TEST_REGION).unwrap();
+        assert_eq!(new_virt_addr, virt_addr);
+
+        assert_eq!(mmu::kernel_unmap_mmio(new_virt_addr, 0x100), Ok(()));
+    }
+}
+
+/// A mapping shared by several users must stay until the last user unmaps it.
+#[kernel_test]
+fn shared_mapping_is_unmapped_last() {
+    unsafe {
+        let first = mmu::kernel_map_mmio("Test 1", &TEST_REGION).unwrap();
+        let second = mmu::kernel_map_mmio("Test 2", &TEST_REGION).unwrap();
+        assert_eq!(first, second);
+
+        assert_eq!(mmu::kernel_unmap_mmio(second, 0x100), Ok(()));
+        assert!(is_mapped(first));
+
+        assert_eq!(mmu::kernel_unmap_mmio(first, 0x100), Ok(()));
+        assert!(!is_mapped(first));
+    }
+}
+
+/// Once init is over, unmapping must fail instead of panicking, and leave the mapping alone.
+///
+/// Must stay the last test, because it ends the init phase.
+#[kernel_test]
+fn unmap_after_init_is_rejected() {
+    unsafe {
+        let virt_addr = mmu::kernel_map_mmio("Test", &TEST_REGION).unwrap();
+        state::state_manager().transition_to_single_core_main();
+
+        assert!(mmu::kernel_unmap_mmio(virt_addr, 0x100).is_err());
+        assert!(is_mapped(virt_addr));
+    }
+}
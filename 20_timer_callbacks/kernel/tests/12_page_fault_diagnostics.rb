@@ -0,0 +1,37 @@
+# frozen_string_literal: true
+
+# SPDX-License-Identifier: MIT OR Apache-2.0
+#
+# Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+require 'console_io_test'
+
+# Verify that the fault is decoded.
+class PageFaultDecodeTest < SubtestBase
+    def name
+        'Page fault is decoded'
+    end
+
+    def run(qemu_out, _qemu_in)
+        expect_or_raise(qemu_out, 'Data abort at VA')
+        expect_or_raise(qemu_out, 'cause: translation fault level 3')
+    end
+end
+
+# Verify that a nearby mapping is pointed out.
+class PageFaultHintTest < SubtestBase
+    def name
+        'Nearby mapping is hinted'
+    end
+
+    def run(qemu_out, _qemu_in)
+        expect_or_raise(qemu_out, 'Hint: Address is just above the mapping of Page fault test')
+    end
+end
+
+##--------------------------------------------------------------------------------------------------
+## Test registration
+##--------------------------------------------------------------------------------------------------
+def subtest_collection
+    [PageFaultDecodeTest.new, PageFaultHintTest.new]
+end
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Page faults must be reported with decoded diagnostics.
+
+#![feature(format_args_nl)]
+#![no_main]
+#![no_std]
+
+/// Console tests should time out on the I/O harness in case of panic.
+mod panic_wait_forever;
+
+use libkernel::{
+    bsp, cpu, exception, info, memory,
+    memory::{mmu::MMIODescriptor, Address},
+};
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    // A region that is only mapped, never accessed. The page following it is not mapped.
+    let mmio_descriptor = MMIODescriptor::new(Address::new(0x1000_0000), 0x100);
+    let virt_addr = memory::mmu::kernel_map_mmio("Page fault test", &mmio_descriptor).unwrap();
+
+    info!("Reading from the page after a known mapping...");
+    let bad_addr = virt_addr + bsp::memory::mmu::KernelGranule::SIZE;
+    core::ptr::read_volatile(bad_addr.as_usize() as *mut u64);
+
+    // The QEMU process running this test will be closed by the I/O test harness.
+    cpu::wait_forever()
+}
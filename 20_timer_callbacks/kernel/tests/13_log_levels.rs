@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Log level filtering test.
+//!
+//! Log output goes to a capturing console first, so that suppressed output can be detected. The
+//! UART is brought up afterwards to report the results.
+
+#![feature(format_args_nl)]
+#![no_main]
+#![no_std]
+
+/// Console tests should time out on the I/O harness in case of panic.
+mod panic_wait_forever;
+
+use core::{
+    fmt,
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+};
+use libkernel::{bsp, console, cpu, error, exception, info, log, memory, println, warn};
+
+//--------------------------------------------------------------------------------------------------
+// Capturing console
+//--------------------------------------------------------------------------------------------------
+
+const CAPTURE_SIZE: usize = 128;
+
+struct CaptureConsole {
+    buf: [AtomicU8; CAPTURE_SIZE],
+    len: AtomicUsize,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU8 = AtomicU8::new(0);
+
+static CAPTURE_CONSOLE: CaptureConsole = CaptureConsole {
+    buf: [ZERO; CAPTURE_SIZE],
+    len: AtomicUsize::new(0),
+};
+
+impl CaptureConsole {
+    fn reset(&self) {
+        self.len.store(0, Ordering::Relaxed);
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    fn byte(&self, i: usize) -> u8 {
+        self.buf[i].load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Write for &CaptureConsole {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            console::interface::Write::write_char(*self, c);
+        }
+
+        Ok(())
+    }
+}
+
+impl console::interface::Write for CaptureConsole {
+    fn write_char(&self, c: char) {
+        let i = self.len.fetch_add(1, Ordering::Relaxed);
+        if i < CAPTURE_SIZE {
+            self.buf[i].store(c as u8, Ordering::Relaxed);
+        }
+    }
+
+    fn write_array(&self, a: &[char]) {
+        for c in a {
+            self.write_char(*c);
+        }
+    }
+
+    fn write_fmt(&self, args: fmt::Arguments) -> fmt::Result {
+        fmt::Write::write_fmt(&mut &*self, args)
+    }
+
+    fn flush(&self) {}
+}
+
+impl console::interface::Read for CaptureConsole {
+    fn clear_rx(&self) {}
+}
+
+impl console::interface::Statistics for CaptureConsole {}
+impl console::interface::All for CaptureConsole {}
+
+//--------------------------------------------------------------------------------------------------
+// Test
+//--------------------------------------------------------------------------------------------------
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+
+    console::register_console(&CAPTURE_CONSOLE);
+
+    log::set_max_level(log::Level::Warn);
+
+    CAPTURE_CONSOLE.reset();
+    info!("Must be suppressed");
+    let info_len = CAPTURE_CONSOLE.len();
+
+    CAPTURE_CONSOLE.reset();
+    warn!("Must be printed");
+    let warn_tag = CAPTURE_CONSOLE.byte(1);
+
+    CAPTURE_CONSOLE.reset();
+    error!("Must be printed");
+    let error_tag = CAPTURE_CONSOLE.byte(1);
+
+    // Unfiltered printing must not be affected.
+    CAPTURE_CONSOLE.reset();
+    println!("x");
+    let println_len = CAPTURE_CONSOLE.len();
+
+    log::set_max_level(log::Level::Info);
+    bsp::driver::qemu_bring_up_console();
+
+    assert_eq!(info_len, 0);
+    assert_eq!(warn_tag, b'W');
+    assert_eq!(error_tag, b'E');
+    assert_eq!(println_len, 2);
+    info!("Log levels are filtered");
+
+    // The QEMU process running this test will be closed by the I/O test harness.
+    cpu::wait_forever()
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! System Timer sanity tests.
+
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(libkernel::test_runner)]
+
+use core::time::Duration;
+use libkernel::{bsp, cpu, driver, exception, memory, time};
+use test_macros::kernel_test;
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    if let Err(x) = time::init() {
+        panic!("Error initializing timer subsystem: {}", x);
+    }
+
+    if let Err(x) = bsp::driver::init() {
+        panic!("Error initializing BSP driver subsystem: {}", x);
+    }
+
+    driver::driver_manager().init_drivers_and_irqs();
+    exception::asynchronous::local_irq_unmask();
+
+    test_main();
+
+    cpu::qemu_exit_success()
+}
+
+/// The free-running counter must advance.
+#[kernel_test]
+fn counter_advances() {
+    let system_timer = unsafe { bsp::driver::system_timer() };
+
+    let t1 = system_timer.now_micros();
+    time::time_manager().spin_for(Duration::from_millis(1));
+    let t2 = system_timer.now_micros();
+
+    assert!(t2 > t1);
+}
+
+/// Channels used by the VideoCore must be rejected.
+#[kernel_test]
+fn reserved_channels_are_rejected() {
+    let system_timer = unsafe { bsp::driver::system_timer() };
+
+    assert!(system_timer.set_compare(0, 0).is_err());
+    assert!(system_timer.set_compare(2, 0).is_err());
+}
+
+/// A compare match on channel 1 must be delivered as an IRQ and acknowledged by the driver.
+#[kernel_test]
+fn compare_match_raises_irq() {
+    use bsp::exception::asynchronous::irq_map;
+    use exception::asynchronous::{interface::IRQManager, irq_manager};
+
+    let system_timer = unsafe { bsp::driver::system_timer() };
+    let count_before = irq_manager().count(&irq_map::SYSTEM_TIMER_C1);
+
+    // The compare registers only match the low 32 bits, which may wrap around.
+    let target = (system_timer.now_micros() as u32).wrapping_add(1000);
+    assert!(system_timer.set_compare(1, target).is_ok());
+    time::time_manager().spin_for(Duration::from_millis(10));
+
+    assert!(irq_manager().count(&irq_map::SYSTEM_TIMER_C1) > count_before);
+    assert!(!system_timer.has_matched(1));
+}
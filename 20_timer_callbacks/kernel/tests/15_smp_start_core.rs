@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Secondary core bring-up tests.
+
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(libkernel::test_runner)]
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use libkernel::{bsp, cpu, exception, memory, state, time};
+use test_macros::kernel_test;
+
+/// The MPIDR reported by core 1, plus one so that zero means "not reported".
+static CORE_1_MPIDR: AtomicU64 = AtomicU64::new(0);
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    if let Err(x) = time::init() {
+        panic!("Error initializing timer subsystem: {}", x);
+    }
+
+    state::state_manager().transition_to_single_core_main();
+
+    test_main();
+
+    cpu::qemu_exit_success()
+}
+
+fn core_1_main() -> ! {
+    CORE_1_MPIDR.store(cpu::smp::mpidr() + 1, Ordering::Release);
+
+    cpu::wait_forever()
+}
+
+/// The boot core and nonexistent cores can not be started.
+#[kernel_test]
+fn invalid_cores_are_rejected() {
+    assert!(cpu::smp::start_core(0, core_1_main).is_err());
+    assert!(cpu::smp::start_core(4, core_1_main).is_err());
+    assert!(!state::state_manager().is_multi_core_main());
+}
+
+/// Core 1 must come up, run its entry, and can only be started once.
+#[kernel_test]
+fn core_1_starts() {
+    assert!(cpu::smp::start_core(1, core_1_main).is_ok());
+    assert!(state::state_manager().is_multi_core_main());
+
+    while CORE_1_MPIDR.load(Ordering::Acquire) == 0 {
+        cpu::nop();
+    }
+    assert_eq!((CORE_1_MPIDR.load(Ordering::Acquire) - 1) & 0b11, 1);
+
+    assert!(cpu::smp::start_core(1, core_1_main).is_err());
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! Spinlock tests with two cores contending for the lock.
+
+#![feature(custom_test_frameworks)]
+#![no_main]
+#![no_std]
+#![reexport_test_harness_main = "test_main"]
+#![test_runner(libkernel::test_runner)]
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use libkernel::{
+    bsp, cpu, exception, memory, state,
+    synchronization::{interface::Mutex, Spinlock},
+    time,
+};
+use test_macros::kernel_test;
+
+/// Number of increments done by each core.
+const ITERATIONS: u64 = 100_000;
+
+static COUNTER: Spinlock<u64> = Spinlock::new(0);
+static CORE_1_DONE: AtomicBool = AtomicBool::new(false);
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    if let Err(x) = time::init() {
+        panic!("Error initializing timer subsystem: {}", x);
+    }
+
+    state::state_manager().transition_to_single_core_main();
+
+    test_main();
+
+    cpu::qemu_exit_success()
+}
+
+/// Increment the counter non-atomically, so that a broken lock loses updates.
+fn increment_many() {
+    for _ in 0..ITERATIONS {
+        COUNTER.lock(|counter| {
+            let value = unsafe { core::ptr::read_volatile(counter) };
+            cpu::nop();
+            unsafe { core::ptr::write_volatile(counter, value + 1) };
+        });
+    }
+}
+
+fn core_1_main() -> ! {
+    increment_many();
+    CORE_1_DONE.store(true, Ordering::Release);
+
+    cpu::wait_forever()
+}
+
+/// No increment must get lost while both cores hammer the lock.
+#[kernel_test]
+fn contended_increments_are_not_lost() {
+    assert!(cpu::smp::start_core(1, core_1_main).is_ok());
+
+    increment_many();
+    while !CORE_1_DONE.load(Ordering::Acquire) {
+        cpu::nop();
+    }
+
+    assert!(!COUNTER.is_locked());
+    assert_eq!(COUNTER.lock(|counter| *counter), 2 * ITERATIONS);
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2022 Andre Richter <andre.o.richter@gmail.com>
+
+//! A panic while the console lock is held must still be reported.
+
+#![feature(format_args_nl)]
+#![no_main]
+#![no_std]
+
+/// Console tests should time out on the I/O harness in case of panic.
+mod panic_wait_forever;
+
+use core::fmt;
+use libkernel::{bsp, cpu, exception, info, memory, println};
+
+/// Panics when being formatted, which happens while the console lock is held.
+struct PanickingDisplay;
+
+impl fmt::Display for PanickingDisplay {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        panic!("Panic with the console lock held")
+    }
+}
+
+#[no_mangle]
+unsafe fn kernel_init() -> ! {
+    exception::handling_init();
+    memory::init();
+    bsp::driver::qemu_bring_up_console();
+
+    info!("Printing a value that panics...");
+    println!("{}", PanickingDisplay);
+
+    // The QEMU process running this test will be closed by the I/O test harness.
+    cpu::wait_forever()
+}